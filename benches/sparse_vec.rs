@@ -0,0 +1,138 @@
+//! Benchmarks for sparse vec lookups.
+//!
+//! `baseline` is a plain vector of slots, the representation sparse vecs used
+//! before paging was added, and is the reference for the flat mode.
+
+#![allow(missing_docs)]
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use sparsey::entity::{Entity, SparseVec, SparseVecSlot, Version};
+
+const ENTITY_COUNT: u32 = 100_000;
+const PAGE_LEN: usize = 1024;
+
+struct Baseline {
+    slots: Vec<Option<SparseVecSlot>>,
+}
+
+impl Baseline {
+    #[inline]
+    fn get(&self, entity: Entity) -> Option<u32> {
+        self.slots
+            .get(entity.sparse())?
+            .filter(|slot| slot.version == entity.version)
+            .map(|slot| slot.index)
+    }
+
+    #[inline]
+    unsafe fn get_sparse_unchecked(&self, sparse: usize) -> usize {
+        self.slots.get_unchecked(sparse).unwrap_unchecked().dense()
+    }
+}
+
+fn shuffled_entities() -> Vec<Entity> {
+    let mut entities = (0..ENTITY_COUNT)
+        .map(|i| Entity::new(i, Version::FIRST))
+        .collect::<Vec<_>>();
+
+    // Deterministic Fisher-Yates shuffle driven by a xorshift generator.
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+
+    for i in (1..entities.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        entities.swap(i, (state % (i as u64 + 1)) as usize);
+    }
+
+    entities
+}
+
+fn slot(index: usize) -> SparseVecSlot {
+    SparseVecSlot {
+        index: index as u32,
+        version: Version::FIRST,
+    }
+}
+
+fn filled(mut sparse: SparseVec, entities: &[Entity]) -> SparseVec {
+    for (index, entity) in entities.iter().enumerate() {
+        *sparse.get_mut_or_allocate_at(entity.sparse()) = Some(slot(index));
+    }
+
+    sparse
+}
+
+fn bench_get(c: &mut Criterion) {
+    let entities = shuffled_entities();
+
+    let mut baseline = Baseline {
+        slots: vec![None; ENTITY_COUNT as usize],
+    };
+
+    for (index, entity) in entities.iter().enumerate() {
+        baseline.slots[entity.sparse()] = Some(slot(index));
+    }
+
+    let flat = filled(SparseVec::new(), &entities);
+    let paged = filled(SparseVec::paged(PAGE_LEN), &entities);
+
+    let mut group = c.benchmark_group("get");
+
+    group.bench_function("baseline", |b| {
+        b.iter(|| {
+            for &entity in &entities {
+                black_box(baseline.get(black_box(entity)));
+            }
+        });
+    });
+
+    group.bench_function("flat", |b| {
+        b.iter(|| {
+            for &entity in &entities {
+                black_box(flat.get(black_box(entity)));
+            }
+        });
+    });
+
+    group.bench_function("paged", |b| {
+        b.iter(|| {
+            for &entity in &entities {
+                black_box(paged.get(black_box(entity)));
+            }
+        });
+    });
+
+    group.finish();
+
+    let mut group = c.benchmark_group("get_sparse_unchecked");
+
+    group.bench_function("baseline", |b| {
+        b.iter(|| {
+            for &entity in &entities {
+                black_box(unsafe { baseline.get_sparse_unchecked(black_box(entity.sparse())) });
+            }
+        });
+    });
+
+    group.bench_function("flat", |b| {
+        b.iter(|| {
+            for &entity in &entities {
+                black_box(unsafe { flat.get_sparse_unchecked(black_box(entity.sparse())) });
+            }
+        });
+    });
+
+    group.bench_function("paged", |b| {
+        b.iter(|| {
+            for &entity in &entities {
+                black_box(unsafe { paged.get_sparse_unchecked(black_box(entity.sparse())) });
+            }
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_get);
+criterion_main!(benches);
@@ -1,4 +1,5 @@
 use crate::component::ComponentSparseSet;
+use crate::entity::SparseVec;
 use core::any::{self, TypeId};
 use core::cmp::Ordering;
 use core::fmt;
@@ -7,7 +8,10 @@ use core::marker::PhantomData;
 
 /// Holds information about a type.
 #[derive(Clone, Copy)]
-pub struct ComponentData(&'static dyn AbstractComponentData);
+pub struct ComponentData {
+    data: &'static dyn AbstractComponentData,
+    sparse_page_len: Option<usize>,
+}
 
 impl ComponentData {
     /// Returns the type data for type `T`.
@@ -16,27 +20,59 @@ impl ComponentData {
     where
         T: Send + Sync + 'static,
     {
-        Self(&ComponentDataImpl::<T>(PhantomData))
+        Self {
+            data: &ComponentDataImpl::<T>(PhantomData),
+            sparse_page_len: None,
+        }
+    }
+
+    /// Makes the component storage use a paged sparse vec with pages of
+    /// `page_len` slots. See [`SparseVec::paged`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `page_len` is not a power of two.
+    #[must_use]
+    pub const fn with_sparse_page_len(self, page_len: usize) -> Self {
+        assert!(page_len.is_power_of_two(), "page_len must be a power of two");
+
+        Self {
+            data: self.data,
+            sparse_page_len: Some(page_len),
+        }
+    }
+
+    /// Returns the page length of the sparse vec used by the component
+    /// storage, or [`None`] if the sparse vec is flat.
+    #[inline]
+    #[must_use]
+    pub const fn sparse_page_len(&self) -> Option<usize> {
+        self.sparse_page_len
     }
 
     /// Returns the type id of the type used in [`new`](Self::new).
     #[inline]
     #[must_use]
     pub fn type_id(&self) -> TypeId {
-        self.0.type_id()
+        self.data.type_id()
     }
 
     /// Returns the type name of the type used in [`new`](Self::new).
     #[inline]
     #[must_use]
     pub fn type_name(&self) -> &'static str {
-        self.0.type_name()
+        self.data.type_name()
     }
 
     #[inline]
     #[must_use]
     pub(crate) fn create_sparse_set(&self) -> ComponentSparseSet {
-        self.0.create_sparse_set()
+        let sparse = match self.sparse_page_len {
+            Some(page_len) => SparseVec::paged(page_len),
+            None => SparseVec::new(),
+        };
+
+        self.data.create_sparse_set(sparse)
     }
 }
 
@@ -61,7 +97,7 @@ impl PartialOrd for ComponentData {
 impl Ord for ComponentData {
     #[inline]
     fn cmp(&self, other: &Self) -> Ordering {
-        self.type_id().cmp(&other.0.type_id())
+        self.type_id().cmp(&other.type_id())
     }
 }
 
@@ -79,7 +115,8 @@ impl fmt::Debug for ComponentData {
         f.debug_struct(stringify!(ComponentData))
             .field("type_id", &self.type_id())
             .field("type_name", &self.type_name())
-            .finish()
+            .field("sparse_page_len", &self.sparse_page_len)
+            .finish_non_exhaustive()
     }
 }
 
@@ -91,7 +128,7 @@ unsafe trait AbstractComponentData: Send + Sync + 'static {
     fn type_name(&self) -> &'static str;
 
     #[must_use]
-    fn create_sparse_set(&self) -> ComponentSparseSet;
+    fn create_sparse_set(&self, sparse: SparseVec) -> ComponentSparseSet;
 }
 
 struct ComponentDataImpl<T>(PhantomData<*const T>);
@@ -116,7 +153,7 @@ where
         any::type_name::<T>()
     }
 
-    fn create_sparse_set(&self) -> ComponentSparseSet {
-        ComponentSparseSet::new::<T>()
+    fn create_sparse_set(&self, sparse: SparseVec) -> ComponentSparseSet {
+        ComponentSparseSet::with_sparse::<T>(sparse)
     }
 }
@@ -0,0 +1,65 @@
+use crate::entity::Entity;
+use atomic_refcell::AtomicRef;
+use core::fmt;
+use core::ops::Deref;
+
+/// Shared reference to a single component in a
+/// [`World`](crate::world::World).
+pub struct ComponentRef<'a, T> {
+    component: AtomicRef<'a, T>,
+}
+
+impl<'a, T> ComponentRef<'a, T> {
+    #[inline]
+    #[must_use]
+    pub(crate) fn new(component: AtomicRef<'a, T>) -> Self {
+        Self { component }
+    }
+}
+
+impl<T> Deref for ComponentRef<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.component
+    }
+}
+
+impl<T> fmt::Debug for ComponentRef<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+/// Shared reference to the entities of a component storage in a
+/// [`World`](crate::world::World).
+pub struct EntitiesRef<'a> {
+    entities: AtomicRef<'a, [Entity]>,
+}
+
+impl<'a> EntitiesRef<'a> {
+    #[inline]
+    #[must_use]
+    pub(crate) fn new(entities: AtomicRef<'a, [Entity]>) -> Self {
+        Self { entities }
+    }
+}
+
+impl Deref for EntitiesRef<'_> {
+    type Target = [Entity];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.entities
+    }
+}
+
+impl fmt::Debug for EntitiesRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
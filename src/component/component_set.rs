@@ -1,4 +1,4 @@
-use crate::component::{group, panic_missing_comp, ungroup, Component, GroupMask};
+use crate::component::{group, panic_missing_comp, ungroup, Component, ComponentData, GroupMask};
 use crate::entity::Entity;
 use crate::World;
 use core::any::TypeId;
@@ -13,6 +13,15 @@ pub unsafe trait ComponentSet {
     /// The components returned by [`remove`](Self::remove) operations.
     type Remove;
 
+    /// The type data of the components in the set.
+    ///
+    /// Defaults to an empty slice for sets that don't report their components.
+    /// Such sets skip the layout check of
+    /// [`World::extend_strict`](crate::World::extend_strict), don't trigger
+    /// required components and are always inserted by
+    /// [`World::insert_if_absent`](crate::World::insert_if_absent).
+    const COMPONENTS: &'static [ComponentData] = &[];
+
     /// Adds the given `components` to `entity`.
     unsafe fn insert(world: &mut World, entity: Entity, components: Self);
 
@@ -41,6 +50,8 @@ macro_rules! impl_component_set {
         {
             type Remove = ($(Option<$Comp>,)*);
 
+            const COMPONENTS: &'static [ComponentData] = &[$(ComponentData::new::<$Comp>(),)*];
+
             unsafe fn insert(world: &mut World, entity: Entity, components: Self) {
                 let mut group_mask = GroupMask::EMPTY;
 
@@ -1,8 +1,16 @@
 use crate::component::Component;
 use crate::entity::{Entity, SparseVec, SparseVecSlot};
+use ::alloc::boxed::Box;
+use ::alloc::format;
+use ::alloc::string::String;
+use ::alloc::vec::Vec;
 use alloc::{alloc, Layout, LayoutError};
+use core::cmp::Ordering;
 use core::ptr::NonNull;
-use core::{fmt, mem, slice};
+use core::{any, fmt, mem, slice};
+
+pub(crate) type InsertHook = Box<dyn FnMut(Entity, NonNull<u8>) + Send + Sync>;
+pub(crate) type RemoveHook = Box<dyn FnMut(Entity) + Send + Sync>;
 
 pub(crate) struct ComponentSparseSet {
     sparse: SparseVec,
@@ -10,57 +18,86 @@ pub(crate) struct ComponentSparseSet {
     components: NonNull<u8>,
     len: usize,
     cap: usize,
+    generation: u64,
     vtable: ComponentSparseSetVtable,
+    on_insert: Option<InsertHook>,
+    on_remove: Option<RemoveHook>,
 }
 
 impl ComponentSparseSet {
     #[must_use]
     pub const fn new<T>() -> Self
+    where
+        T: Component,
+    {
+        Self::with_sparse::<T>(SparseVec::new())
+    }
+
+    #[must_use]
+    pub const fn with_sparse<T>(sparse: SparseVec) -> Self
     where
         T: Component,
     {
         Self {
-            sparse: SparseVec::new(),
+            sparse,
             entities: NonNull::dangling(),
             components: NonNull::<T>::dangling().cast(),
             len: 0,
             cap: 0,
+            generation: 0,
             vtable: ComponentSparseSetVtable::new::<T>(),
+            on_insert: None,
+            on_remove: None,
         }
     }
 
+    #[inline]
+    pub fn set_on_insert(&mut self, hook: InsertHook) {
+        self.on_insert = Some(hook);
+    }
+
+    #[inline]
+    pub fn set_on_remove(&mut self, hook: RemoveHook) {
+        self.on_remove = Some(hook);
+    }
+
     pub unsafe fn insert<T>(&mut self, entity: Entity, component: T) -> Option<T>
     where
         T: Component,
     {
         let slot = self.sparse.get_mut_or_allocate_at(entity.sparse());
 
-        match slot {
-            Some(slot) => {
-                let index = slot.dense();
+        let (index, prev_component) = if let Some(slot) = slot {
+            let index = slot.dense();
+
+            // Replace existing entity and component.
+            *self.entities.add(index).as_mut() = entity;
+            let prev_component = self.components.cast::<T>().add(index).replace(component);
+            (index, Some(prev_component))
+        } else {
+            *slot = Some(SparseVecSlot {
+                index: self.len as u32,
+                version: entity.version,
+            });
 
-                // Replace existing entity and component.
-                *self.entities.add(index).as_mut() = entity;
-                Some(self.components.cast::<T>().add(index).replace(component))
+            if self.len == self.cap {
+                self.grow();
             }
-            None => {
-                *slot = Some(SparseVecSlot {
-                    index: self.len as u32,
-                    version: entity.version,
-                });
-
-                if self.len == self.cap {
-                    self.grow();
-                }
 
-                // Write entity and component to uninitialized memory.
-                self.entities.add(self.len).write(entity);
-                self.components.cast::<T>().add(self.len).write(component);
+            // Write entity and component to uninitialized memory.
+            self.entities.add(self.len).write(entity);
+            self.components.cast::<T>().add(self.len).write(component);
 
-                self.len += 1;
-                None
-            }
+            self.len += 1;
+            self.generation += 1;
+            (self.len - 1, None)
+        };
+
+        if let Some(on_insert) = &mut self.on_insert {
+            on_insert(entity, self.components.cast::<T>().add(index).cast());
         }
+
+        prev_component
     }
 
     pub unsafe fn remove<T>(&mut self, entity: Entity) -> Option<T>
@@ -70,6 +107,7 @@ impl ComponentSparseSet {
         let raw_index = self.sparse.remove(entity)?;
         let index = raw_index as usize;
         self.len -= 1;
+        self.generation += 1;
 
         let last_entity = *self.entities.add(self.len).as_ref();
         *self.entities.add(index).as_mut() = last_entity;
@@ -87,6 +125,11 @@ impl ComponentSparseSet {
         // Replace removed component with last component.
         let component = removed_ptr.read();
         last_ptr.copy_to(removed_ptr, 1);
+
+        if let Some(on_remove) = &mut self.on_remove {
+            on_remove(entity);
+        }
+
         Some(component)
     }
 
@@ -100,6 +143,7 @@ impl ComponentSparseSet {
 
         let index = raw_index as usize;
         self.len -= 1;
+        self.generation += 1;
 
         let last_entity = *self.entities.add(self.len).as_ref();
         *self.entities.add(index).as_mut() = last_entity;
@@ -116,6 +160,88 @@ impl ComponentSparseSet {
 
         let last_ptr = self.components.cast::<T>().add(self.len);
         last_ptr.copy_to(dropped_ptr, 1);
+
+        if let Some(on_remove) = &mut self.on_remove {
+            on_remove(entity);
+        }
+    }
+
+    #[must_use]
+    pub unsafe fn drain<T>(&mut self) -> Vec<(Entity, T)>
+    where
+        T: Component,
+    {
+        let mut items = Vec::with_capacity(self.len);
+
+        for i in 0..self.len {
+            items.push((
+                *self.entities.add(i).as_ref(),
+                self.components.cast::<T>().add(i).read(),
+            ));
+        }
+
+        self.sparse.clear();
+        self.len = 0;
+        self.generation += 1;
+
+        if let Some(on_remove) = &mut self.on_remove {
+            for &(entity, _) in &items {
+                on_remove(entity);
+            }
+        }
+
+        items
+    }
+
+    pub unsafe fn truncate<T>(&mut self, len: usize)
+    where
+        T: Component,
+    {
+        if len >= self.len {
+            return;
+        }
+
+        let old_len = mem::replace(&mut self.len, len);
+        self.generation += 1;
+
+        for i in len..old_len {
+            let entity = *self.entities.add(i).as_ref();
+            self.sparse.remove_sparse(entity.sparse());
+            self.components.cast::<T>().add(i).drop_in_place();
+        }
+
+        if let Some(on_remove) = &mut self.on_remove {
+            for i in len..old_len {
+                on_remove(*self.entities.add(i).as_ref());
+            }
+        }
+    }
+
+    pub unsafe fn sort_by<T, F>(&mut self, mut compare: F)
+    where
+        T: Component,
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let components = self.components.cast::<T>();
+        let mut order = (0..self.len).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| compare(components.add(a).as_ref(), components.add(b).as_ref()));
+
+        // Apply the permutation by following its cycles.
+        for i in 0..self.len {
+            let mut current = i;
+
+            loop {
+                let target = order[current];
+                order[current] = current;
+
+                if target == i {
+                    break;
+                }
+
+                self.swap_typed::<T>(current, target);
+                current = target;
+            }
+        }
     }
 
     #[inline]
@@ -137,6 +263,7 @@ impl ComponentSparseSet {
 
     #[inline]
     #[must_use]
+    #[allow(clippy::mut_from_ref)]
     pub unsafe fn get_mut<T>(&self, entity: Entity) -> Option<&mut T>
     where
         T: Component,
@@ -145,6 +272,25 @@ impl ComponentSparseSet {
         Some(self.components.cast::<T>().add(dense).as_mut())
     }
 
+    #[inline]
+    #[must_use]
+    pub fn get_raw(&self, entity: Entity) -> Option<NonNull<u8>> {
+        let dense = self.sparse.get(entity)? as usize;
+        unsafe { Some(self.components.byte_add(dense * self.vtable.layout.size())) }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn as_raw_slice(&self) -> (NonNull<u8>, usize, usize) {
+        (self.components, self.len, self.vtable.layout.size())
+    }
+
+    pub fn remap(&mut self, f: impl FnMut(Entity) -> Entity) {
+        let entities = unsafe { slice::from_raw_parts_mut(self.entities.as_ptr(), self.len) };
+        self.sparse.remap(entities, f);
+        self.generation += 1;
+    }
+
     #[inline]
     #[must_use]
     pub fn contains(&self, entity: Entity) -> bool {
@@ -181,6 +327,27 @@ impl ComponentSparseSet {
         slice::from_raw_parts_mut(self.components.cast::<T>().as_ptr(), self.len)
     }
 
+    #[inline]
+    #[must_use]
+    pub unsafe fn as_slices<T>(&self) -> (&[Entity], &[T])
+    where
+        T: Component,
+    {
+        (self.entities(), self.as_slice::<T>())
+    }
+
+    #[inline]
+    #[must_use]
+    pub unsafe fn as_mut_slices<T>(&mut self) -> (&[Entity], &mut [T])
+    where
+        T: Component,
+    {
+        (
+            slice::from_raw_parts(self.entities.as_ptr(), self.len),
+            slice::from_raw_parts_mut(self.components.cast::<T>().as_ptr(), self.len),
+        )
+    }
+
     #[inline]
     #[must_use]
     pub unsafe fn as_non_null_ptr<T>(&self) -> NonNull<T>
@@ -202,6 +369,50 @@ impl ComponentSparseSet {
         self.len == 0
     }
 
+    #[inline]
+    #[must_use]
+    pub fn type_name(&self) -> &'static str {
+        (self.vtable.type_name)()
+    }
+
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let type_name = self.type_name();
+
+        for (dense, &entity) in self.entities().iter().enumerate() {
+            if self.sparse.get(entity) != Some(dense as u32) {
+                return Err(format!(
+                    "Component '{type_name}': entity {entity:#} at dense index {dense} is not \
+                     mapped back to it",
+                ));
+            }
+        }
+
+        for sparse in 0..self.sparse.slots_len() {
+            let Some(dense) = self.sparse.get_sparse(sparse) else {
+                continue;
+            };
+
+            if self
+                .entities()
+                .get(dense as usize)
+                .is_none_or(|entity| entity.sparse() != sparse)
+            {
+                return Err(format!(
+                    "Component '{type_name}': sparse index {sparse} is mapped to dense index \
+                     {dense}, which doesn't hold an entity with that index",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
     #[inline]
     pub unsafe fn swap(&mut self, a: usize, b: usize) {
         unsafe {
@@ -209,6 +420,22 @@ impl ComponentSparseSet {
         }
     }
 
+    pub fn swap_dense(&mut self, a: usize, b: usize) {
+        assert!(
+            a < self.len && b < self.len,
+            "dense index out of bounds: the len is {} but the indexes are {} and {}",
+            self.len,
+            a,
+            b,
+        );
+
+        if a != b {
+            unsafe {
+                self.swap(a, b);
+            }
+        }
+    }
+
     #[inline]
     pub fn clear(&mut self) {
         unsafe {
@@ -246,11 +473,14 @@ impl ComponentSparseSet {
                 alloc::handle_alloc_error(new_layout);
             };
 
-            (
-                new_data.cast::<Entity>(),
-                new_data.byte_add(new_components_offset).cast::<T>(),
-                new_cap,
-            )
+            // Zero-sized components don't need dense storage.
+            let new_components = if mem::size_of::<T>() == 0 {
+                NonNull::<T>::dangling()
+            } else {
+                new_data.byte_add(new_components_offset).cast::<T>()
+            };
+
+            (new_data.cast::<Entity>(), new_components, new_cap)
         };
 
         // Copy old entities and components to new location.
@@ -279,6 +509,7 @@ impl ComponentSparseSet {
         debug_assert!(dense_a < self.len);
         debug_assert!(dense_b < self.len);
         debug_assert_ne!(dense_a, dense_b);
+        self.generation += 1;
 
         // Swap entities.
         let entity_a = self.entities.add(dense_a).as_mut();
@@ -298,16 +529,22 @@ impl ComponentSparseSet {
         T: Component,
     {
         self.sparse.clear();
+        let len = mem::replace(&mut self.len, 0);
+        self.generation += 1;
 
         if mem::needs_drop::<T>() {
-            for i in 0..self.len {
+            for i in 0..len {
                 unsafe {
                     self.components.cast::<T>().add(i).drop_in_place();
                 }
             }
         }
 
-        self.len = 0;
+        if let Some(on_remove) = &mut self.on_remove {
+            for i in 0..len {
+                on_remove(*self.entities.add(i).as_ref());
+            }
+        }
     }
 
     unsafe fn drop_typed<T>(&mut self)
@@ -331,6 +568,11 @@ impl ComponentSparseSet {
     fn compute_layout<T>(cap: usize) -> (Layout, usize) {
         fn compute_layout_impl<T>(cap: usize) -> Result<(Layout, usize), LayoutError> {
             let entities_layout = Layout::array::<Entity>(cap)?;
+
+            if mem::size_of::<T>() == 0 {
+                return Ok((entities_layout, entities_layout.size()));
+            }
+
             let components_layout = Layout::array::<T>(cap)?;
             entities_layout.extend(components_layout)
         }
@@ -369,6 +611,8 @@ impl fmt::Debug for ComponentSparseSet {
 
 #[derive(Clone, Copy)]
 struct ComponentSparseSetVtable {
+    type_name: fn() -> &'static str,
+    layout: Layout,
     grow: unsafe fn(&mut ComponentSparseSet),
     swap: unsafe fn(&mut ComponentSparseSet, usize, usize),
     delete: unsafe fn(&mut ComponentSparseSet, Entity),
@@ -382,6 +626,8 @@ impl ComponentSparseSetVtable {
         T: Component,
     {
         Self {
+            type_name: any::type_name::<T>,
+            layout: Layout::new::<T>(),
             grow: ComponentSparseSet::grow_typed::<T>,
             swap: ComponentSparseSet::swap_typed::<T>,
             delete: ComponentSparseSet::delete::<T>,
@@ -390,3 +636,64 @@ impl ComponentSparseSetVtable {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::Version;
+
+    #[test]
+    fn test_check_invariants_high_page() {
+        let mut sparse_set = ComponentSparseSet::with_sparse::<u32>(SparseVec::paged(16));
+        let e0 = Entity::new(1, Version::FIRST);
+        let e1 = Entity::new(100, Version::FIRST);
+
+        unsafe {
+            sparse_set.insert(e0, 0_u32);
+            sparse_set.insert(e1, 1_u32);
+        }
+
+        assert_eq!(sparse_set.check_invariants(), Ok(()));
+
+        // Stale slot on a page past the allocated page count
+        *sparse_set.sparse.get_mut_or_allocate_at(120) = Some(SparseVecSlot {
+            index: 0,
+            version: Version::FIRST,
+        });
+
+        assert_eq!(
+            sparse_set.check_invariants(),
+            Err(String::from(
+                "Component 'u32': sparse index 120 is mapped to dense index 0, which doesn't \
+                 hold an entity with that index",
+            )),
+        );
+    }
+
+    #[test]
+    fn test_check_invariants_unmapped_entity() {
+        let mut sparse_set = ComponentSparseSet::new::<u32>();
+        let e0 = Entity::new(0, Version::FIRST);
+        let e1 = Entity::new(1, Version::FIRST);
+
+        unsafe {
+            sparse_set.insert(e0, 0_u32);
+            sparse_set.insert(e1, 1_u32);
+        }
+
+        // Point the second entity at the dense index of the first one
+        sparse_set
+            .sparse
+            .get_mut_or_allocate_at(1)
+            .as_mut()
+            .unwrap()
+            .index = 0;
+
+        assert_eq!(
+            sparse_set.check_invariants(),
+            Err(String::from(
+                "Component 'u32': entity 1v1 at dense index 1 is not mapped back to it",
+            )),
+        );
+    }
+}
@@ -1,13 +1,16 @@
 use crate::component::{
-    group, ungroup_all, Component, ComponentData, ComponentSparseSet, Group, GroupInfo,
-    GroupLayout, GroupMask, GroupMetadata, NonZeroStorageMask, QueryGroupInfo, QueryMask,
-    StorageMask, View, ViewMut,
+    group, ungroup, ungroup_all, Component, ComponentData, ComponentRef, ComponentSparseSet,
+    EntitiesRef, Group, GroupInfo, GroupLayout, GroupMask, GroupMetadata, NonZeroStorageMask,
+    QueryGroupInfo, QueryMask, StorageMask, View, ViewMut,
 };
 use crate::entity::Entity;
+use alloc::format;
+use alloc::string::String;
 use alloc::vec::Vec;
-use atomic_refcell::AtomicRefCell;
+use atomic_refcell::{AtomicRef, AtomicRefCell};
 use core::any::{self, TypeId};
 use core::ops::Range;
+use core::ptr::NonNull;
 use core::{cmp, mem};
 use hashbrown::hash_map::Entry;
 use hashbrown::HashMap;
@@ -20,6 +23,8 @@ pub(crate) struct ComponentStorage {
     pub(crate) groups: Vec<Group>,
     pub(crate) metadata: FxHashMap<TypeId, ComponentMetadata>,
     pub(crate) components: Vec<AtomicRefCell<ComponentSparseSet>>,
+    pub(crate) stable_ids: FxHashMap<TypeId, u16>,
+    pub(crate) stable_id_types: FxHashMap<u16, TypeId>,
 }
 
 impl ComponentStorage {
@@ -66,6 +71,7 @@ impl ComponentStorage {
                     self.metadata.insert(
                         component.type_id(),
                         ComponentMetadata {
+                            component: *component,
                             storage_index: self.components.len(),
                             insert_mask: GroupMask::from_to(group_start, group_end),
                             delete_mask: GroupMask::from_to(new_group_start, group_end),
@@ -79,7 +85,7 @@ impl ComponentStorage {
 
                     let sparse_set = sparse_sets
                         .remove(&component.type_id())
-                        .unwrap_or_else(|| component.create_sparse_set());
+                        .map_or_else(|| component.create_sparse_set(), |(_, s)| s);
 
                     self.components.push(AtomicRefCell::new(sparse_set));
                 }
@@ -88,10 +94,11 @@ impl ComponentStorage {
             }
         }
 
-        for (type_id, sparse_set) in sparse_sets {
+        for (type_id, (component, sparse_set)) in sparse_sets {
             self.metadata.insert(
                 type_id,
                 ComponentMetadata {
+                    component,
                     storage_index: self.components.len(),
                     insert_mask: GroupMask::default(),
                     delete_mask: GroupMask::default(),
@@ -117,6 +124,7 @@ impl ComponentStorage {
         };
 
         entry.insert(ComponentMetadata {
+            component,
             storage_index: self.components.len(),
             insert_mask: GroupMask::default(),
             delete_mask: GroupMask::default(),
@@ -129,12 +137,51 @@ impl ComponentStorage {
         true
     }
 
+    #[inline]
+    #[must_use]
+    pub fn component_data(&self, type_id: TypeId) -> Option<ComponentData> {
+        self.metadata
+            .get(&type_id)
+            .map(|metadata| metadata.component)
+    }
+
     #[inline]
     #[must_use]
     pub fn is_registered_dyn(&self, type_id: TypeId) -> bool {
         self.metadata.contains_key(&type_id)
     }
 
+    pub fn assign_stable_id(&mut self, type_id: TypeId, stable_id: u16) -> bool {
+        match (
+            self.stable_ids.get(&type_id),
+            self.stable_id_types.get(&stable_id),
+        ) {
+            (None, None) => {
+                self.stable_ids.insert(type_id, stable_id);
+                self.stable_id_types.insert(stable_id, type_id);
+                true
+            }
+            (Some(&old_stable_id), Some(&old_type_id)) => {
+                old_stable_id == stable_id && old_type_id == type_id
+            }
+            _ => false,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_grouped_dyn(&self, type_id: TypeId) -> bool {
+        self.metadata
+            .get(&type_id)
+            .is_some_and(|metadata| metadata.group_info.is_some())
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn group_info(&self, type_id: TypeId) -> Option<GroupInfo> {
+        self.metadata.get(&type_id)?.group_info
+    }
+
     pub fn strip(&mut self, entity: Entity) {
         unsafe {
             ungroup_all(&mut self.components, &mut self.groups, entity);
@@ -145,6 +192,12 @@ impl ComponentStorage {
         }
     }
 
+    pub fn remap(&mut self, mut f: impl FnMut(Entity) -> Entity) {
+        for sparse_set in &mut self.components {
+            sparse_set.get_mut().remap(&mut f);
+        }
+    }
+
     pub fn clear(&mut self) {
         for group in &mut self.groups {
             group.len = 0;
@@ -156,7 +209,227 @@ impl ComponentStorage {
     }
 
     #[must_use]
-    pub fn borrow<T>(&self) -> View<T>
+    pub fn sparse_set_mut<T>(&mut self) -> &mut ComponentSparseSet
+    where
+        T: Component,
+    {
+        let Some(metadata) = self.metadata.get(&TypeId::of::<T>()) else {
+            panic_missing_comp::<T>();
+        };
+
+        unsafe {
+            self.components
+                .get_unchecked_mut(metadata.storage_index)
+                .get_mut()
+        }
+    }
+
+    #[must_use]
+    pub fn drain<T>(&mut self) -> Vec<(Entity, T)>
+    where
+        T: Component,
+    {
+        self.ungroup_all_of::<T>();
+        unsafe { self.sparse_set_mut::<T>().drain::<T>() }
+    }
+
+    #[must_use]
+    pub fn remove_many<T>(&mut self, entities: &[Entity]) -> Vec<Option<T>>
+    where
+        T: Component,
+    {
+        let Some(metadata) = self.metadata.get(&TypeId::of::<T>()) else {
+            panic_missing_comp::<T>();
+        };
+
+        let group_mask = metadata.delete_mask;
+        let sparse_set = unsafe {
+            self.components
+                .get_unchecked(metadata.storage_index)
+                .as_ptr()
+        };
+
+        entities
+            .iter()
+            .map(|&entity| unsafe {
+                if group_mask != GroupMask::EMPTY {
+                    ungroup(&mut self.components, &mut self.groups, group_mask, entity);
+                }
+
+                (*sparse_set).remove::<T>(entity)
+            })
+            .collect()
+    }
+
+    pub fn clear_component<T>(&mut self)
+    where
+        T: Component,
+    {
+        self.ungroup_all_of::<T>();
+        self.sparse_set_mut::<T>().clear();
+    }
+
+    fn ungroup_all_of<T>(&mut self)
+    where
+        T: Component,
+    {
+        let Some(metadata) = self.metadata.get(&TypeId::of::<T>()) else {
+            panic_missing_comp::<T>();
+        };
+
+        // Removing all components of type `T` empties every group containing
+        // `T`, without moving entities in the other storages.
+        for group_index in metadata.delete_mask.iter_bit_indexes() {
+            unsafe {
+                self.groups.get_unchecked_mut(group_index as usize).len = 0;
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn get_raw(&self, type_id: TypeId, entity: Entity) -> Option<NonNull<u8>> {
+        let metadata = self.metadata.get(&type_id)?;
+
+        unsafe {
+            self.components
+                .get_unchecked(metadata.storage_index)
+                .borrow()
+                .get_raw(entity)
+        }
+    }
+
+    #[must_use]
+    pub fn get<T>(&self, entity: Entity) -> Option<ComponentRef<'_, T>>
+    where
+        T: Component,
+    {
+        let Some(metadata) = self.metadata.get(&TypeId::of::<T>()) else {
+            panic_missing_comp::<T>();
+        };
+
+        let sparse_set = unsafe {
+            self.components
+                .get_unchecked(metadata.storage_index)
+                .borrow()
+        };
+
+        AtomicRef::filter_map(sparse_set, |sparse_set| unsafe {
+            sparse_set.get::<T>(entity)
+        })
+        .map(ComponentRef::new)
+    }
+
+    pub fn check_invariants(&self, is_alive: impl Fn(Entity) -> bool) -> Result<(), String> {
+        let storages = self
+            .components
+            .iter()
+            .map(AtomicRefCell::borrow)
+            .collect::<Vec<_>>();
+
+        for storage in &storages {
+            storage.check_invariants()?;
+
+            if let Some(entity) = storage.entities().iter().find(|&&e| !is_alive(e)) {
+                return Err(format!(
+                    "Component '{}': entity {entity:#} is not in the world",
+                    storage.type_name(),
+                ));
+            }
+        }
+
+        for (group_index, group) in self.groups.iter().enumerate() {
+            let storages = &storages[group.metadata.storage_range()];
+
+            if let Some(storage) = storages.iter().find(|s| s.len() < group.len) {
+                return Err(format!(
+                    "Group {group_index}: length {} exceeds the length of component '{}'",
+                    group.len,
+                    storage.type_name(),
+                ));
+            }
+
+            let grouped = &storages[0].entities()[..group.len];
+
+            for storage in &storages[1..] {
+                let Some(dense) = (0..group.len).find(|&i| storage.entities()[i] != grouped[i])
+                else {
+                    continue;
+                };
+
+                return Err(format!(
+                    "Group {group_index}: component '{}' holds entity {:#} at dense index \
+                     {dense}, expected {:#}",
+                    storage.type_name(),
+                    storage.entities()[dense],
+                    grouped[dense],
+                ));
+            }
+
+            let ungrouped = &storages[0].entities()[group.len..];
+
+            if let Some(entity) = ungrouped
+                .iter()
+                .find(|&&e| storages[1..].iter().all(|s| s.contains(e)))
+            {
+                return Err(format!(
+                    "Group {group_index}: entity {entity:#} has all grouped components but is \
+                     not grouped",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn slice_raw(&self, type_id: TypeId) -> Option<(NonNull<u8>, usize, usize)> {
+        let metadata = self.metadata.get(&type_id)?;
+
+        unsafe {
+            Some(
+                self.components
+                    .get_unchecked(metadata.storage_index)
+                    .borrow()
+                    .as_raw_slice(),
+            )
+        }
+    }
+
+    #[must_use]
+    pub fn generation(&self, type_id: TypeId) -> Option<u64> {
+        let metadata = self.metadata.get(&type_id)?;
+
+        unsafe {
+            Some(
+                self.components
+                    .get_unchecked(metadata.storage_index)
+                    .borrow()
+                    .generation(),
+            )
+        }
+    }
+
+    #[must_use]
+    pub fn entities_with<T>(&self) -> Option<EntitiesRef<'_>>
+    where
+        T: Component,
+    {
+        let metadata = self.metadata.get(&TypeId::of::<T>())?;
+
+        let components = unsafe {
+            self.components
+                .get_unchecked(metadata.storage_index)
+                .borrow()
+        };
+
+        Some(EntitiesRef::new(AtomicRef::map(
+            components,
+            ComponentSparseSet::entities,
+        )))
+    }
+
+    #[must_use]
+    pub fn borrow<T>(&self) -> View<'_, T>
     where
         T: Component,
     {
@@ -174,7 +447,7 @@ impl ComponentStorage {
     }
 
     #[must_use]
-    pub fn borrow_mut<T>(&self) -> ViewMut<T>
+    pub fn borrow_mut<T>(&self) -> ViewMut<'_, T>
     where
         T: Component,
     {
@@ -192,7 +465,7 @@ impl ComponentStorage {
     }
 
     #[must_use]
-    pub fn borrow_with_group_info<T>(&self) -> (View<T>, Option<GroupInfo>)
+    pub fn borrow_with_group_info<T>(&self) -> (View<'_, T>, Option<GroupInfo>)
     where
         T: Component,
     {
@@ -212,7 +485,7 @@ impl ComponentStorage {
     }
 
     #[must_use]
-    pub fn borrow_with_group_info_mut<T>(&self) -> (ViewMut<T>, Option<GroupInfo>)
+    pub fn borrow_with_group_info_mut<T>(&self) -> (ViewMut<'_, T>, Option<GroupInfo>)
     where
         T: Component,
     {
@@ -292,7 +565,7 @@ impl ComponentStorage {
     }
 
     #[must_use]
-    fn extract_sparse_sets(&mut self) -> FxHashMap<TypeId, ComponentSparseSet> {
+    fn extract_sparse_sets(&mut self) -> FxHashMap<TypeId, (ComponentData, ComponentSparseSet)> {
         let sparse_sets = self
             .metadata
             .drain()
@@ -302,7 +575,7 @@ impl ComponentStorage {
                     ComponentSparseSet::new::<()>(),
                 );
 
-                (type_id, sparse_set)
+                (type_id, (metadata.component, sparse_set))
             })
             .collect::<FxHashMap<_, _>>();
 
@@ -315,6 +588,7 @@ impl ComponentStorage {
 
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct ComponentMetadata {
+    pub component: ComponentData,
     pub storage_index: usize,
     pub insert_mask: GroupMask,
     pub delete_mask: GroupMask,
@@ -2,7 +2,7 @@ use crate::component::NonZeroStorageMask;
 use core::cmp;
 
 /// Grouping information for any number of views in a query.
-#[derive(Clone, Copy, Default, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
 pub enum QueryGroupInfo {
     /// The query is empty.
     #[default]
@@ -71,7 +71,7 @@ impl QueryGroupInfo {
 }
 
 /// Stores the length and grouping information for a component view.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub struct ViewGroupInfo {
     /// The group info of the view, if any.
     pub info: Option<GroupInfo>,
@@ -80,7 +80,10 @@ pub struct ViewGroupInfo {
 }
 
 /// Grouping information for one or more views.
-#[derive(Clone, Copy, Debug)]
+///
+/// Group infos compare equal if they refer to the same groups and storages of
+/// the same layout, so they should only be compared within the same world.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub struct GroupInfo {
     pub(crate) group_start: u8,
     pub(crate) group_end: u8,
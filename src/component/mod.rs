@@ -1,6 +1,7 @@
 //! Component storage and management.
 
 mod component_data;
+mod component_ref;
 mod component_set;
 mod component_sparse_set;
 mod component_storage;
@@ -12,6 +13,7 @@ mod storage_mask;
 mod view;
 
 pub use self::component_data::*;
+pub use self::component_ref::*;
 pub use self::component_set::*;
 pub use self::group_info::*;
 pub use self::group_layout::*;
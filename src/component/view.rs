@@ -3,6 +3,7 @@ use crate::entity::{Entity, SparseVec};
 use atomic_refcell::{AtomicRef, AtomicRefMut};
 use core::fmt;
 use core::marker::PhantomData;
+use core::mem;
 use core::ops::{Index, IndexMut};
 use core::ptr::NonNull;
 
@@ -51,11 +52,48 @@ where
         unsafe { self.components.get_mut::<T>(entity) }
     }
 
+    /// Replaces the component mapped to `entity` with `value`, returning the
+    /// previous component.
+    ///
+    /// Returns [`None`] if `entity` is missing from the view, in which case
+    /// `value` is dropped.
+    pub fn replace(&mut self, entity: Entity, value: T) -> Option<T> {
+        self.get_mut(entity)
+            .map(|component| mem::replace(component, value))
+    }
+
+    /// Returns mutable references to the components mapped to `a` and `b`.
+    ///
+    /// Returns [`None`] if `a` and `b` share the same index or if any of them
+    /// is missing from the view.
+    #[must_use]
+    pub fn get_two_mut(&mut self, a: Entity, b: Entity) -> Option<(&mut T, &mut T)> {
+        if a.index == b.index {
+            return None;
+        }
+
+        let dense_a = self.components.sparse().get(a)? as usize;
+        let dense_b = self.components.sparse().get(b)? as usize;
+
+        unsafe {
+            let ptr = self.components.as_non_null_ptr::<T>();
+            Some((ptr.add(dense_a).as_mut(), ptr.add(dense_b).as_mut()))
+        }
+    }
+
     /// Returns a mutable slice of all components in the view.
     #[must_use]
     pub fn as_mut_slice(&mut self) -> &mut [T] {
         unsafe { self.components.as_mut_slice::<T>() }
     }
+
+    /// Returns a slice of all entities and a mutable slice of all components
+    /// in the view. The entity at each index owns the component at the same
+    /// index.
+    #[must_use]
+    pub fn as_mut_slices(&mut self) -> (&[Entity], &mut [T]) {
+        unsafe { self.components.as_mut_slices::<T>() }
+    }
 }
 
 impl<T> IndexMut<Entity> for ViewMut<'_, T>
@@ -91,12 +129,27 @@ macro_rules! impl_view_common {
                 self.components.len()
             }
 
+            /// Returns whether `index` is a valid index into the slices of the
+            /// view, i.e. whether it is less than [`len`](Self::len).
+            #[must_use]
+            pub fn is_valid_dense_index(&self, index: usize) -> bool {
+                index < self.components.len()
+            }
+
             /// Returns whether the view is empty.
             #[must_use]
             pub fn is_empty(&self) -> bool {
                 self.components.is_empty()
             }
 
+            /// Returns the structural generation of the storage, which changes
+            /// whenever entities are added, removed or reordered, but not when
+            /// components are modified in place.
+            #[must_use]
+            pub fn structural_generation(&self) -> u64 {
+                self.components.generation()
+            }
+
             /// Returns a slice of all entities in the view.
             #[must_use]
             pub fn entities(&self) -> &[Entity] {
@@ -109,6 +162,13 @@ macro_rules! impl_view_common {
                 unsafe { self.components.as_slice::<T>() }
             }
 
+            /// Returns slices of all entities and components in the view. The
+            /// entity at each index owns the component at the same index.
+            #[must_use]
+            pub fn as_slices(&self) -> (&[Entity], &[T]) {
+                unsafe { self.components.as_slices::<T>() }
+            }
+
             #[must_use]
             pub(crate) fn sparse(&self) -> &SparseVec {
                 self.components.sparse()
@@ -1,5 +1,8 @@
 use crate::entity::Entity;
 use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem;
 use core::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 
 #[derive(Default, Debug)]
@@ -8,6 +11,10 @@ pub(crate) struct EntityAllocator {
     last_maintained_index: u64,
     recycled: VecDeque<Entity>,
     recycled_since_maintain: AtomicUsize,
+    // New indexes handed out by `allocate` while atomic allocations were
+    // pending. They must not be returned again by `maintain`. Sorted, since
+    // new indexes are handed out in increasing order.
+    allocated_since_maintain: Vec<u32>,
 }
 
 impl EntityAllocator {
@@ -16,10 +23,19 @@ impl EntityAllocator {
         let recycled_since_maintain = *self.recycled_since_maintain.get_mut();
 
         if recycled_since_maintain < self.recycled.len() {
-            *self.recycled_since_maintain.get_mut() += 1;
-            Some(self.recycled[self.recycled.len() - recycled_since_maintain - 1])
+            // Entities reserved atomically sit at the back of the queue, so
+            // take the one right before them.
+            self.recycled
+                .remove(self.recycled.len() - recycled_since_maintain - 1)
         } else if let Ok(index) = u32::try_from(*self.next_index_to_allocate.get_mut()) {
             *self.next_index_to_allocate.get_mut() += 1;
+
+            if self.last_maintained_index == u64::from(index) {
+                self.last_maintained_index += 1;
+            } else {
+                self.allocated_since_maintain.push(index);
+            }
+
             Some(Entity::with_index(index))
         } else {
             None
@@ -60,9 +76,78 @@ impl EntityAllocator {
             new_index_range
         };
 
-        self.recycled
-            .drain(recycled_range)
-            .chain(new_index_range.map(|i| Entity::with_index(i as u32)))
+        let allocated_since_maintain = mem::take(&mut self.allocated_since_maintain);
+
+        self.recycled.drain(recycled_range).chain(
+            new_index_range
+                .map(|i| i as u32)
+                .filter(move |i| allocated_since_maintain.binary_search(i).is_err())
+                .map(Entity::with_index),
+        )
+    }
+
+    /// Returns whether each index below the next new index can be allocated
+    /// again.
+    #[must_use]
+    pub fn free_mask(&self) -> Vec<bool> {
+        let mut is_free = vec![false; self.next_index_to_allocate.load(Ordering::Relaxed) as usize];
+
+        for entity in &self.recycled {
+            is_free[entity.sparse()] = true;
+        }
+
+        is_free
+    }
+
+    /// Updates the free indexes after live entities were moved to the sorted
+    /// `occupied` indexes, recycling the `vacated` entities. Must be called
+    /// right after [`maintain`](Self::maintain).
+    pub fn remap(&mut self, occupied: &[u32], vacated: &[Entity]) {
+        debug_assert_eq!(*self.recycled_since_maintain.get_mut(), 0);
+
+        let is_occupied = |index: u32| occupied.binary_search(&index).is_ok();
+        self.recycled.retain(|entity| !is_occupied(entity.index));
+
+        let next_index_to_allocate = *self.next_index_to_allocate.get_mut();
+        let new_next_index_to_allocate = occupied
+            .last()
+            .map_or(0, |&index| u64::from(index) + 1)
+            .max(next_index_to_allocate);
+
+        // Indexes skipped over by the occupied ones become free.
+        for index in next_index_to_allocate..new_next_index_to_allocate {
+            let index = index as u32;
+
+            if !is_occupied(index) {
+                self.recycled.push_front(Entity::with_index(index));
+            }
+        }
+
+        *self.next_index_to_allocate.get_mut() = new_next_index_to_allocate;
+        self.last_maintained_index = new_next_index_to_allocate;
+
+        for &entity in vacated {
+            self.recycle(entity);
+        }
+    }
+
+    /// Returns how many times each allocated index was recycled, given the
+    /// `entities` currently alive. Indices that can no longer be allocated
+    /// until the allocator is reset are reported as `u32::MAX`.
+    #[must_use]
+    pub fn reuse_counts(&self, entities: &[Entity]) -> Vec<u32> {
+        let allocated_len = self.next_index_to_allocate.load(Ordering::Relaxed) as usize;
+        let mut counts = vec![u32::MAX; allocated_len];
+
+        // Indices allocated since the last maintain are still on their first
+        // version, unless they are recycled below.
+        counts[self.last_maintained_index as usize..].fill(0);
+
+        for entity in self.recycled.iter().chain(entities) {
+            counts[entity.sparse()] = entity.version.0.get() - 1;
+        }
+
+        counts
     }
 
     pub fn reset(&mut self) {
@@ -70,6 +155,7 @@ impl EntityAllocator {
         self.last_maintained_index = 0;
         self.recycled.clear();
         *self.recycled_since_maintain.get_mut() = 0;
+        self.allocated_since_maintain.clear();
     }
 
     fn increment_recycled_since_maintain(&self) -> Option<usize> {
@@ -0,0 +1,114 @@
+use crate::entity::{Entity, Version};
+use hashbrown::hash_map::Entry;
+use hashbrown::HashMap;
+use rustc_hash::FxBuildHasher;
+
+/// Maps entities to values, hashing only their indexes.
+///
+/// The version of each entity is stored alongside its value, so looking up a
+/// stale entity that shares its index with a newer one returns [`None`].
+#[derive(Clone, Debug)]
+pub struct EntityIndexMap<V> {
+    values: HashMap<u32, (Version, V), FxBuildHasher>,
+}
+
+impl<V> EntityIndexMap<V> {
+    /// Creates a new, empty map.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maps `entity` to `value`, replacing any value mapped to an entity with
+    /// the same index.
+    ///
+    /// Returns the value previously mapped to `entity`, if any.
+    pub fn insert(&mut self, entity: Entity, value: V) -> Option<V> {
+        match self.values.entry(entity.index) {
+            Entry::Occupied(mut entry) => {
+                let (version, prev_value) = entry.insert((entity.version, value));
+                (version == entity.version).then_some(prev_value)
+            }
+            Entry::Vacant(entry) => {
+                entry.insert((entity.version, value));
+                None
+            }
+        }
+    }
+
+    /// Returns a reference to the value mapped to `entity`, if any.
+    #[must_use]
+    pub fn get(&self, entity: Entity) -> Option<&V> {
+        self.values
+            .get(&entity.index)
+            .filter(|(version, _)| *version == entity.version)
+            .map(|(_, value)| value)
+    }
+
+    /// Returns a mutable reference to the value mapped to `entity`, if any.
+    #[must_use]
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut V> {
+        self.values
+            .get_mut(&entity.index)
+            .filter(|(version, _)| *version == entity.version)
+            .map(|(_, value)| value)
+    }
+
+    /// Returns whether the map contains `entity`.
+    #[must_use]
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.get(entity).is_some()
+    }
+
+    /// Removes `entity` from the map.
+    ///
+    /// Returns the value mapped to `entity`, if any.
+    pub fn remove(&mut self, entity: Entity) -> Option<V> {
+        let Entry::Occupied(entry) = self.values.entry(entity.index) else {
+            return None;
+        };
+
+        if entry.get().0 != entity.version {
+            return None;
+        }
+
+        Some(entry.remove().1)
+    }
+
+    /// Returns the number of entities in the map.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns whether the map is empty.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Removes all entities from the map.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+
+    /// Returns an iterator over all entities and their values.
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &V)> {
+        self.values
+            .iter()
+            .map(|(&index, (version, value))| (Entity::new(index, *version), value))
+    }
+}
+
+impl<V> Default for EntityIndexMap<V> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            values: HashMap::default(),
+        }
+    }
+}
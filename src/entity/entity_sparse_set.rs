@@ -12,22 +12,21 @@ impl EntitySparseSet {
     pub fn insert(&mut self, entity: Entity) -> Option<Entity> {
         let dense_entity = self.sparse.get_mut_or_allocate_at(entity.sparse());
 
-        match dense_entity {
-            Some(dense_entity) => unsafe {
+        if let Some(dense_entity) = dense_entity {
+            unsafe {
                 Some(mem::replace(
                     self.entities.get_unchecked_mut(dense_entity.index as usize),
                     entity,
                 ))
-            },
-            None => {
-                *dense_entity = Some(SparseVecSlot {
-                    index: self.entities.len() as u32,
-                    version: entity.version,
-                });
-
-                self.entities.push(entity);
-                None
             }
+        } else {
+            *dense_entity = Some(SparseVecSlot {
+                index: self.entities.len() as u32,
+                version: entity.version,
+            });
+
+            self.entities.push(entity);
+            None
         }
     }
 
@@ -51,6 +50,18 @@ impl EntitySparseSet {
         true
     }
 
+    pub fn remap(&mut self, f: impl FnMut(Entity) -> Entity) {
+        self.sparse.remap(&mut self.entities, f);
+    }
+
+    #[cfg(feature = "fixedbitset")]
+    #[inline]
+    #[must_use]
+    pub fn get_by_index(&self, index: usize) -> Option<Entity> {
+        let dense = self.sparse.get_sparse(index)?;
+        unsafe { Some(*self.entities.get_unchecked(dense as usize)) }
+    }
+
     #[inline]
     #[must_use]
     pub fn contains(&self, entity: Entity) -> bool {
@@ -1,4 +1,7 @@
+#[cfg(test)]
+use crate::entity::Version;
 use crate::entity::{Entity, EntityAllocator, EntitySparseSet};
+use alloc::vec::Vec;
 
 #[derive(Default, Debug)]
 pub(crate) struct EntityStorage {
@@ -18,6 +21,21 @@ impl EntityStorage {
         entity
     }
 
+    /// Creates an entity with the given `version`, as if its index had been
+    /// recycled that many times.
+    #[cfg(test)]
+    #[must_use]
+    pub fn create_with_version(&mut self, version: Version) -> Entity {
+        let entity = self
+            .allocator
+            .allocate()
+            .expect("No entities left to allocate");
+
+        let entity = Entity::new(entity.index, version);
+        self.entities.insert(entity);
+        entity
+    }
+
     #[must_use]
     pub fn create_atomic(&self) -> Entity {
         self.allocator
@@ -36,6 +54,12 @@ impl EntityStorage {
         self.entities.contains(entity)
     }
 
+    #[cfg(feature = "fixedbitset")]
+    #[must_use]
+    pub fn get_by_index(&self, index: usize) -> Option<Entity> {
+        self.entities.get_by_index(index)
+    }
+
     #[must_use]
     pub fn len(&self) -> usize {
         self.entities.len()
@@ -51,6 +75,11 @@ impl EntityStorage {
         self.entities.as_slice()
     }
 
+    #[must_use]
+    pub fn reuse_counts(&self) -> Vec<u32> {
+        self.allocator.reuse_counts(self.entities.as_slice())
+    }
+
     pub fn remove(&mut self, entity: Entity) -> bool {
         if !self.entities.remove(entity) {
             return false;
@@ -60,6 +89,16 @@ impl EntityStorage {
         true
     }
 
+    #[must_use]
+    pub fn free_mask(&self) -> Vec<bool> {
+        self.allocator.free_mask()
+    }
+
+    pub fn remap(&mut self, f: impl FnMut(Entity) -> Entity, occupied: &[u32], vacated: &[Entity]) {
+        self.entities.remap(f);
+        self.allocator.remap(occupied, vacated);
+    }
+
     pub fn clear(&mut self) {
         let _ = self.allocator.maintain();
         self.entities.clear();
@@ -1,6 +1,7 @@
 //! Entity storage and allocation.
 
 mod entity_allocator;
+mod entity_index_map;
 mod entity_sparse_set;
 mod entity_storage;
 mod sparse_vec;
@@ -8,6 +9,7 @@ mod sparse_vec;
 #[cfg(feature = "bitcode")]
 use bitcode::{Decode, Encode};
 
+pub use self::entity_index_map::*;
 pub use self::sparse_vec::*;
 
 pub(crate) use self::entity_allocator::*;
@@ -83,9 +85,15 @@ impl fmt::Debug for Entity {
     }
 }
 
+/// Formats the entity as its index. The alternate flag (`{:#}`) also includes
+/// the version, as in `42v3`.
 impl fmt::Display for Entity {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.index)
+        if f.alternate() {
+            write!(f, "{}v{}", self.index, self.version.0)
+        } else {
+            write!(f, "{}", self.index)
+        }
     }
 }
 
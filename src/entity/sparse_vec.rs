@@ -1,11 +1,34 @@
 use crate::entity::{Entity, Version};
+use alloc::boxed::Box;
+use alloc::vec;
 use alloc::vec::Vec;
-use core::{fmt, iter, mem};
+use core::{fmt, iter};
 
 /// Maps entities to dense indexes.
+///
+/// Sparse vecs are flat by default. Paged sparse vecs, created with
+/// [`paged`](Self::paged), split the index space into fixed-size pages that
+/// are allocated lazily, which saves memory when only a few high indexes are
+/// in use.
 #[derive(Clone, Default)]
 pub struct SparseVec {
-    slots: Vec<Option<SparseVecSlot>>,
+    slots: SparseSlots,
+}
+
+#[derive(Clone)]
+enum SparseSlots {
+    Flat(Vec<Option<SparseVecSlot>>),
+    Paged {
+        page_shift: u32,
+        pages: Vec<Option<Box<[Option<SparseVecSlot>]>>>,
+    },
+}
+
+impl Default for SparseSlots {
+    #[inline]
+    fn default() -> Self {
+        Self::Flat(Vec::new())
+    }
 }
 
 impl SparseVec {
@@ -13,15 +36,70 @@ impl SparseVec {
     #[inline]
     #[must_use]
     pub const fn new() -> Self {
-        Self { slots: Vec::new() }
+        Self {
+            slots: SparseSlots::Flat(Vec::new()),
+        }
+    }
+
+    /// Creates a new paged sparse vec with pages of `page_len` slots.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `page_len` is not a power of two.
+    #[inline]
+    #[must_use]
+    pub const fn paged(page_len: usize) -> Self {
+        assert!(
+            page_len.is_power_of_two(),
+            "page_len must be a power of two"
+        );
+
+        Self {
+            slots: SparseSlots::Paged {
+                page_shift: page_len.trailing_zeros(),
+                pages: Vec::new(),
+            },
+        }
+    }
+
+    /// Returns the length of the pages, or [`None`] if the sparse vec is flat.
+    #[inline]
+    #[must_use]
+    pub fn page_len(&self) -> Option<usize> {
+        match &self.slots {
+            SparseSlots::Flat(_) => None,
+            SparseSlots::Paged { page_shift, .. } => Some(1 << page_shift),
+        }
+    }
+
+    /// Returns the number of slots currently allocated by the sparse vec.
+    #[must_use]
+    pub fn allocated_len(&self) -> usize {
+        match &self.slots {
+            SparseSlots::Flat(slots) => slots.len(),
+            SparseSlots::Paged { page_shift, pages } => {
+                pages.iter().filter(|page| page.is_some()).count() << page_shift
+            }
+        }
+    }
+
+    /// Returns an iterator over whether each sparse index is occupied, up to
+    /// and including the highest occupied index. Useful for inspecting
+    /// fragmentation.
+    pub fn occupancy(&self) -> impl Iterator<Item = bool> + '_ {
+        let len = (0..self.slots_len())
+            .rev()
+            .find(|&i| self.contains_sparse(i))
+            .map_or(0, |i| i + 1);
+
+        (0..len).map(|i| self.contains_sparse(i))
     }
 
     /// Returns the dense index mapped to `entity`, if any.
     #[inline]
     #[must_use]
     pub fn get(&self, entity: Entity) -> Option<u32> {
-        self.slots
-            .get(entity.sparse())?
+        self.slot(entity.sparse())?
             .filter(|slot| slot.version == entity.version)
             .map(|slot| slot.index)
     }
@@ -30,8 +108,7 @@ impl SparseVec {
     #[inline]
     #[must_use]
     pub fn contains(&self, entity: Entity) -> bool {
-        self.slots
-            .get(entity.sparse())
+        self.slot(entity.sparse())
             .and_then(Option::as_ref)
             .is_some_and(|slot| slot.version == entity.version)
     }
@@ -41,8 +118,7 @@ impl SparseVec {
     /// Returns the dense index mapped to `entity`, if any.
     #[inline]
     pub fn remove(&mut self, entity: Entity) -> Option<u32> {
-        self.slots
-            .get_mut(entity.sparse())?
+        self.slot_mut(entity.sparse())?
             .take_if(|slot| slot.version == entity.version)
             .map(|slot| slot.index)
     }
@@ -51,7 +127,7 @@ impl SparseVec {
     #[inline]
     #[must_use]
     pub fn get_sparse(&self, sparse: usize) -> Option<u32> {
-        self.slots.get(sparse)?.map(|slot| slot.index)
+        self.slot(sparse)?.map(|slot| slot.index)
     }
 
     /// Returns the dense index at the given sparse index without checking if it
@@ -59,14 +135,14 @@ impl SparseVec {
     #[inline]
     #[must_use]
     pub unsafe fn get_sparse_unchecked(&self, sparse: usize) -> usize {
-        self.slots.get_unchecked(sparse).unwrap_unchecked().dense()
+        self.slot_unchecked(sparse).unwrap_unchecked().dense()
     }
 
     /// Returns whether the sparse vec contains the given sparse index.
     #[inline]
     #[must_use]
     pub fn contains_sparse(&self, sparse: usize) -> bool {
-        self.slots.get(sparse).and_then(Option::as_ref).is_some()
+        self.slot(sparse).and_then(Option::as_ref).is_some()
     }
 
     /// Removes the dense entity at the given sparse index.
@@ -74,63 +150,160 @@ impl SparseVec {
     /// Returns the removed dense entity, if any.
     #[inline]
     pub fn remove_sparse(&mut self, sparse: usize) -> Option<SparseVecSlot> {
-        self.slots.get_mut(sparse)?.take()
+        self.slot_mut(sparse)?.take()
     }
 
     /// Returns the entity slot at the given dense index without checking if it
     /// is valid.
     #[inline]
     pub unsafe fn get_unchecked_mut(&mut self, index: usize) -> &mut Option<SparseVecSlot> {
-        self.slots.get_unchecked_mut(index)
+        self.slot_unchecked_mut(index)
     }
 
     /// Returns or allocates the entity slot at the given dense index.
     #[inline]
     pub fn get_mut_or_allocate_at(&mut self, index: usize) -> &mut Option<SparseVecSlot> {
-        if index >= self.slots.len() {
-            self.extend_to_index(index);
-        }
+        match &mut self.slots {
+            SparseSlots::Flat(slots) => {
+                if index >= slots.len() {
+                    Self::extend_to_index(slots, index);
+                }
+
+                unsafe { slots.get_unchecked_mut(index) }
+            }
+            SparseSlots::Paged { page_shift, pages } => {
+                let page_index = index >> *page_shift;
+
+                if page_index >= pages.len() {
+                    pages.resize_with(page_index + 1, || None);
+                }
 
-        unsafe { self.slots.get_unchecked_mut(index) }
+                let page = unsafe { pages.get_unchecked_mut(page_index) }
+                    .get_or_insert_with(|| vec![None; 1 << *page_shift].into_boxed_slice());
+
+                unsafe { page.get_unchecked_mut(index & ((1 << *page_shift) - 1)) }
+            }
+        }
     }
 
     /// Swaps the entities at the given dense indexes without checking their
     /// validity.
     #[inline]
     pub unsafe fn swap_nonoverlapping(&mut self, a: usize, b: usize) {
-        debug_assert!(a < self.slots.len());
-        debug_assert!(b < self.slots.len());
+        debug_assert!(self.slot(a).is_some());
+        debug_assert!(self.slot(b).is_some());
         debug_assert_ne!(a, b);
 
-        let entity_a = &mut *self.slots.as_mut_ptr().add(a);
-        let entity_b = &mut *self.slots.as_mut_ptr().add(b);
-        mem::swap(entity_a, entity_b);
+        let slot_a = *self.slot_unchecked(a);
+        let slot_b = *self.slot_unchecked(b);
+        *self.slot_unchecked_mut(a) = slot_b;
+        *self.slot_unchecked_mut(b) = slot_a;
+    }
+
+    /// Replaces each of the dense `entities` with the entity returned by `f`,
+    /// updating their slots to keep the same dense indexes.
+    pub(crate) fn remap(&mut self, entities: &mut [Entity], mut f: impl FnMut(Entity) -> Entity) {
+        for entity in entities.iter() {
+            self.remove_sparse(entity.sparse());
+        }
+
+        for (index, entity) in entities.iter_mut().enumerate() {
+            *entity = f(*entity);
+
+            *self.get_mut_or_allocate_at(entity.sparse()) = Some(SparseVecSlot {
+                index: index as u32,
+                version: entity.version,
+            });
+        }
     }
 
     /// Removes all entities from the storage.
     #[inline]
     pub fn clear(&mut self) {
-        self.slots.clear();
+        match &mut self.slots {
+            SparseSlots::Flat(slots) => slots.clear(),
+            SparseSlots::Paged { pages, .. } => pages.clear(),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    fn slot(&self, sparse: usize) -> Option<&Option<SparseVecSlot>> {
+        match &self.slots {
+            SparseSlots::Flat(slots) => slots.get(sparse),
+            SparseSlots::Paged { page_shift, pages } => {
+                let page = pages.get(sparse >> page_shift)?.as_deref()?;
+                Some(unsafe { page.get_unchecked(sparse & ((1 << page_shift) - 1)) })
+            }
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    fn slot_mut(&mut self, sparse: usize) -> Option<&mut Option<SparseVecSlot>> {
+        match &mut self.slots {
+            SparseSlots::Flat(slots) => slots.get_mut(sparse),
+            SparseSlots::Paged { page_shift, pages } => {
+                let page = pages.get_mut(sparse >> *page_shift)?.as_deref_mut()?;
+                Some(unsafe { page.get_unchecked_mut(sparse & ((1 << *page_shift) - 1)) })
+            }
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    #[allow(clippy::ref_option)]
+    unsafe fn slot_unchecked(&self, sparse: usize) -> &Option<SparseVecSlot> {
+        match &self.slots {
+            SparseSlots::Flat(slots) => slots.get_unchecked(sparse),
+            SparseSlots::Paged { page_shift, pages } => pages
+                .get_unchecked(sparse >> page_shift)
+                .as_deref()
+                .unwrap_unchecked()
+                .get_unchecked(sparse & ((1 << page_shift) - 1)),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    unsafe fn slot_unchecked_mut(&mut self, sparse: usize) -> &mut Option<SparseVecSlot> {
+        match &mut self.slots {
+            SparseSlots::Flat(slots) => slots.get_unchecked_mut(sparse),
+            SparseSlots::Paged { page_shift, pages } => pages
+                .get_unchecked_mut(sparse >> *page_shift)
+                .as_deref_mut()
+                .unwrap_unchecked()
+                .get_unchecked_mut(sparse & ((1 << *page_shift) - 1)),
+        }
+    }
+
+    /// Returns the number of addressable slots, including the unallocated
+    /// slots of missing pages.
+    #[must_use]
+    pub(crate) fn slots_len(&self) -> usize {
+        match &self.slots {
+            SparseSlots::Flat(slots) => slots.len(),
+            SparseSlots::Paged { page_shift, pages } => pages.len() << page_shift,
+        }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (usize, SparseVecSlot)> + '_ {
+        (0..self.slots_len()).filter_map(|i| Some((i, (*self.slot(i)?)?)))
     }
 
     #[cold]
-    fn extend_to_index(&mut self, index: usize) {
-        let extra_len = index.checked_next_power_of_two().unwrap_or(index) - self.slots.len() + 1;
-        self.slots.extend(iter::repeat(None).take(extra_len));
+    fn extend_to_index(slots: &mut Vec<Option<SparseVecSlot>>, index: usize) {
+        let extra_len = index.checked_next_power_of_two().unwrap_or(index) - slots.len() + 1;
+        slots.extend(iter::repeat_n(None, extra_len));
     }
 }
 
 impl fmt::Debug for SparseVec {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let entries = self
-            .slots
-            .iter()
-            .enumerate()
-            .filter_map(|(i, &dense_entity)| {
-                let dense_entity = dense_entity?;
-                let entity = Entity::new(i as u32, dense_entity.version);
-                Some((entity, dense_entity.index))
-            });
+        let entries = self.iter().map(|(i, dense_entity)| {
+            let entity = Entity::new(i as u32, dense_entity.version);
+            (entity, dense_entity.index)
+        });
 
         f.debug_map().entries(entries).finish()
     }
@@ -29,6 +29,7 @@
 //!
 //! - `std` (on by default): link to the `std` crate.
 //! - `parallel`: enable parallel iterators.
+//! - `fixedbitset`: enable iterating over the entities in a `FixedBitSet`.
 //!
 //! # Usage
 //!
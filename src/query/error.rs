@@ -0,0 +1,24 @@
+use core::error::Error;
+use core::fmt;
+
+/// Error returned when a query expected to match exactly one entity doesn't.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SingleError {
+    /// No entities matched the query.
+    None,
+    /// More than one entity matched the query.
+    Multiple,
+}
+
+impl fmt::Display for SingleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::None => write!(f, "No entities matched the query"),
+            Self::Multiple => write!(f, "More than one entity matched the query"),
+        }
+    }
+}
+
+impl Error for SingleError {
+    // Empty
+}
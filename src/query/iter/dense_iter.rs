@@ -32,6 +32,17 @@ where
             get_data,
         }
     }
+
+    /// Returns the entity of the next item without advancing the iterator or
+    /// creating the item.
+    #[must_use]
+    pub fn peek_entity(&self) -> Option<Entity> {
+        if self.range.is_empty() {
+            return None;
+        }
+
+        unsafe { Some(*self.entities.add(self.range.start).as_ref()) }
+    }
 }
 
 impl<'a, G> Iterator for DenseIter<'a, G>
@@ -97,6 +97,10 @@ where
 
         init
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.entities.len()))
+    }
 }
 
 impl<G, I, E> FusedIterator for SparseIter<'_, G, I, E>
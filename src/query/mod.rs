@@ -1,5 +1,6 @@
 //! Query and iterate entities and components.
 
+mod error;
 mod iter;
 mod query_all;
 mod query_one;
@@ -8,6 +9,7 @@ mod query_part;
 #[cfg(feature = "parallel")]
 mod par_iter;
 
+pub use self::error::*;
 pub use self::iter::*;
 pub use self::query_all::*;
 pub use self::query_one::*;
@@ -16,7 +18,7 @@ pub use self::query_part::*;
 #[cfg(feature = "parallel")]
 pub use self::par_iter::*;
 
-use crate::component::QueryGroupInfo;
+use crate::component::{ComponentData, QueryGroupInfo};
 use crate::entity::Entity;
 use crate::World;
 use core::mem::MaybeUninit;
@@ -45,6 +47,10 @@ pub unsafe trait Query {
     /// Data used for sparse and dense iteration.
     type Data<'a>: Copy;
 
+    /// The type data of the component whose grouping affects each view, if
+    /// any. Only used for diagnostics.
+    const GROUPED_COMPONENTS: &'static [Option<ComponentData>] = &[];
+
     /// Borrows a view from the `world`.
     #[must_use]
     fn borrow(world: &World) -> Self::View<'_>;
@@ -209,6 +215,8 @@ where
     type Sparse<'a> = <Q as QueryPart>::Sparse<'a>;
     type Data<'a> = <Q as QueryPart>::Data<'a>;
 
+    const GROUPED_COMPONENTS: &'static [Option<ComponentData>] = &[Q::GROUPED_COMPONENT];
+
     fn borrow(world: &World) -> Self::View<'_> {
         <Q as QueryPart>::borrow(world)
     }
@@ -290,6 +298,9 @@ macro_rules! impl_query {
             type Sparse<'a> = ($($Ty::Sparse<'a>,)+);
             type Data<'a> = ($($Ty::Data<'a>,)+);
 
+            const GROUPED_COMPONENTS: &'static [Option<ComponentData>] =
+                &[$($Ty::GROUPED_COMPONENT,)+];
+
             fn borrow(world: &World) -> Self::View<'_> {
                 ($($Ty::borrow(world),)+)
             }
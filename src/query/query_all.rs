@@ -1,11 +1,15 @@
-use crate::query::{DenseIter, Iter, Query, QueryGroupInfo, SparseIter};
+use crate::entity::Entity;
+use crate::query::{DenseIter, Iter, Query, QueryGroupInfo, SingleError, SparseIter};
 use crate::World;
+use alloc::vec::Vec;
+use core::mem::MaybeUninit;
 use core::ops::Range;
+use core::{array, iter};
 
 #[cfg(feature = "parallel")]
 use {
     crate::query::{DenseParIter, ParIter, SparseParIter},
-    rayon::iter::ParallelIterator,
+    rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator},
 };
 
 /// Queries all items that match a query.
@@ -92,12 +96,19 @@ where
     }
 }
 
-impl<'a, G, I, E> QueryAll<'a, G, I, E>
+impl<G, I, E> QueryAll<'_, G, I, E>
 where
     G: Query,
     I: Query,
     E: Query,
 {
+    /// Returns whether the query is grouped, meaning it iterates densely over
+    /// contiguous components and can be [`slice`](Self::slice)d.
+    #[must_use]
+    pub fn is_dense(&self) -> bool {
+        self.get_group_range().is_some()
+    }
+
     /// Returns an iterator over all items that match the query.
     pub fn iter(&mut self) -> Iter<'_, G, I, E> {
         if let Some(range) = self.get_group_range() {
@@ -106,22 +117,8 @@ where
             let entities = get_entities.or(include_entities).unwrap();
             unsafe { Iter::Dense(DenseIter::new(range, entities, get_data)) }
         } else {
-            let (get_entities, get_sparse, get_data) = G::split_sparse_parts(&self.get);
-            let (include_entities, include_sparse) = I::split_filter_parts(&self.include);
-            let (_, exclude_sparse) = E::split_filter_parts(&self.exclude);
-
-            let entities = match (get_entities, include_entities) {
-                (Some(get_entities), Some(include_entities)) => {
-                    if get_entities.len() <= include_entities.len() {
-                        get_entities
-                    } else {
-                        include_entities
-                    }
-                }
-                (Some(get_entities), None) => get_entities,
-                (None, Some(include_entities)) => include_entities,
-                (None, None) => &[],
-            };
+            let (entities, exclude_sparse, include_sparse, get_sparse, get_data) =
+                self.split_sparse_parts();
 
             Iter::Sparse(SparseIter::new(
                 entities,
@@ -133,6 +130,159 @@ where
         }
     }
 
+    /// Returns an iterator over all items that match the query, in ascending
+    /// order of their entity indexes.
+    ///
+    /// Unlike [`iter`](Self::iter), the iteration order does not depend on the
+    /// order in which components were added or removed. This requires copying
+    /// and sorting the entities of the shortest view, so it is considerably
+    /// slower than [`iter`](Self::iter).
+    pub fn iter_sorted(&mut self) -> impl Iterator<Item = G::Item<'_>> {
+        let (entities, exclude_sparse, include_sparse, get_sparse, get_data) =
+            self.split_sparse_parts();
+
+        let mut entities = entities.to_vec();
+        entities.sort_unstable_by_key(|entity| entity.index);
+
+        entities.into_iter().filter_map(move |entity| {
+            let sparse = entity.sparse();
+
+            if !E::contains_none_raw(exclude_sparse, sparse) {
+                return None;
+            }
+
+            if !I::contains_all_raw(include_sparse, sparse) {
+                return None;
+            }
+
+            unsafe { G::get_sparse_raw(get_sparse, get_data, entity) }
+        })
+    }
+
+    /// Returns an iterator over all items that match the query, in ascending
+    /// order of the keys returned by `f`. Items with equal keys are yielded in
+    /// ascending order of their entity indexes.
+    ///
+    /// The keys and entities are collected into `scratch`, which is cleared
+    /// first, so the same buffer can be reused across calls to avoid
+    /// allocating.
+    pub fn iter_sorted_by_key<'b, K, F>(
+        &'b mut self,
+        scratch: &'b mut Vec<(K, Entity)>,
+        mut f: F,
+    ) -> impl Iterator<Item = G::Item<'b>> + 'b
+    where
+        K: Ord,
+        F: FnMut(G::Item<'_>) -> K,
+    {
+        let (entities, exclude_sparse, include_sparse, get_sparse, get_data) =
+            self.split_sparse_parts();
+
+        scratch.clear();
+
+        for &entity in entities {
+            let sparse = entity.sparse();
+
+            if !E::contains_none_raw(exclude_sparse, sparse) {
+                continue;
+            }
+
+            if !I::contains_all_raw(include_sparse, sparse) {
+                continue;
+            }
+
+            if let Some(item) = unsafe { G::get_sparse_raw(get_sparse, get_data, entity) } {
+                scratch.push((f(item), entity));
+            }
+        }
+
+        scratch.sort_unstable_by(|(a_key, a), (b_key, b)| {
+            a_key.cmp(b_key).then(a.index.cmp(&b.index))
+        });
+
+        scratch.iter().map(move |&(_, entity)| unsafe {
+            G::get_sparse_raw(get_sparse, get_data, entity).unwrap_unchecked()
+        })
+    }
+
+    /// Returns an iterator over all unordered combinations of `K` distinct
+    /// items that match the query. Each combination is yielded once, with its
+    /// items in iteration order.
+    ///
+    /// Only available for queries with [`Copy`] items, such as read-only
+    /// queries, since a mutable item can't be yielded more than once.
+    pub fn iter_combinations<const K: usize>(
+        &mut self,
+    ) -> impl Iterator<Item = [G::Item<'_>; K]> + '_
+    where
+        for<'b> G::Item<'b>: Copy,
+    {
+        let items = self.iter().collect::<Vec<_>>();
+        let mut indexes = (K <= items.len()).then(|| array::from_fn::<usize, K, _>(|i| i));
+
+        iter::from_fn(move || {
+            let current = indexes?;
+            let combination = current.map(|i| items[i]);
+
+            // Advance the rightmost index that can still move right, then
+            // reset the ones after it.
+            indexes = (0..K)
+                .rev()
+                .find(|&i| current[i] < items.len() - K + i)
+                .map(|i| {
+                    let mut next = current;
+                    next[i] += 1;
+
+                    for j in (i + 1)..K {
+                        next[j] = next[j - 1] + 1;
+                    }
+
+                    next
+                });
+
+            Some(combination)
+        })
+    }
+
+    /// Returns the first item that matches the query, if any.
+    #[must_use]
+    pub fn first(&mut self) -> Option<G::Item<'_>> {
+        self.iter().next()
+    }
+
+    /// Returns the only item that matches the query.
+    ///
+    /// Returns an error if no items or more than one item match the query.
+    pub fn single(&mut self) -> Result<G::Item<'_>, SingleError> {
+        let mut iter = self.iter();
+        let item = iter.next().ok_or(SingleError::None)?;
+
+        if iter.next().is_some() {
+            return Err(SingleError::Multiple);
+        }
+
+        Ok(item)
+    }
+
+    /// Writes items that match the query into `out`, stopping when either the
+    /// query is exhausted or `out` is full.
+    ///
+    /// Returns the number of items written. Only the first elements of `out`
+    /// up to that number are initialized.
+    pub fn write_into<'b>(&'b mut self, out: &mut [MaybeUninit<G::Item<'b>>]) -> usize
+    where
+        G::Item<'b>: Copy,
+    {
+        let mut len = 0;
+
+        for (slot, item) in out.iter_mut().zip(self.iter()) {
+            slot.write(item);
+            len += 1;
+        }
+
+        len
+    }
+
     /// Calls `f` for all items that match the query.
     pub fn for_each<F>(&mut self, f: F)
     where
@@ -150,22 +300,8 @@ where
             let entities = get_entities.or(include_entities).unwrap();
             unsafe { ParIter::Dense(DenseParIter::new(range, entities, get_data)) }
         } else {
-            let (get_entities, get_sparse, get_data) = G::split_sparse_parts(&self.get);
-            let (include_entities, include_sparse) = I::split_filter_parts(&self.include);
-            let (_, exclude_sparse) = E::split_filter_parts(&self.exclude);
-
-            let entities = match (get_entities, include_entities) {
-                (Some(get_entities), Some(include_entities)) => {
-                    if get_entities.len() <= include_entities.len() {
-                        get_entities
-                    } else {
-                        include_entities
-                    }
-                }
-                (Some(get_entities), None) => get_entities,
-                (None, Some(include_entities)) => include_entities,
-                (None, None) => &[],
-            };
+            let (entities, exclude_sparse, include_sparse, get_sparse, get_data) =
+                self.split_sparse_parts();
 
             ParIter::Sparse(SparseParIter::new(
                 entities,
@@ -186,6 +322,46 @@ where
         self.par_iter().for_each(f);
     }
 
+    /// Folds all items that match the query in parallel.
+    ///
+    /// Items are split into consecutive chunks of `chunk_size` in iteration
+    /// order. Each chunk is folded into an accumulator starting from
+    /// `identity()`, then the accumulators are combined with `reduce` in chunk
+    /// order, regardless of which chunk finished first. The result is the same
+    /// as folding the chunks sequentially, which makes floating-point
+    /// reductions reproducible.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0.
+    #[cfg(feature = "parallel")]
+    pub fn par_fold<T, ID, F, R>(
+        &mut self,
+        chunk_size: usize,
+        identity: ID,
+        fold: F,
+        reduce: R,
+    ) -> T
+    where
+        T: Send,
+        ID: Fn() -> T + Send + Sync,
+        F: Fn(T, G::Item<'_>) -> T + Send + Sync,
+        R: FnMut(T, T) -> T,
+    {
+        assert_ne!(chunk_size, 0, "chunk size must be greater than 0");
+
+        let chunks: Vec<T> = match self.par_iter() {
+            ParIter::Sparse(iter) => iter
+                .collect::<Vec<_>>()
+                .into_par_iter()
+                .fold_chunks(chunk_size, &identity, &fold)
+                .collect(),
+            ParIter::Dense(iter) => iter.fold_chunks(chunk_size, &identity, &fold).collect(),
+        };
+
+        chunks.into_iter().reduce(reduce).unwrap_or_else(identity)
+    }
+
     /// Returns ordered slices of all items that match the query, if the query
     /// is grouped.
     #[must_use]
@@ -197,6 +373,37 @@ where
         unsafe { Some(G::slice_raw(get_parts, entities, range)) }
     }
 
+    #[must_use]
+    #[allow(clippy::type_complexity)]
+    fn split_sparse_parts(
+        &self,
+    ) -> (
+        &[Entity],
+        E::Sparse<'_>,
+        I::Sparse<'_>,
+        G::Sparse<'_>,
+        G::Data<'_>,
+    ) {
+        let (get_entities, get_sparse, get_data) = G::split_sparse_parts(&self.get);
+        let (include_entities, include_sparse) = I::split_filter_parts(&self.include);
+        let (_, exclude_sparse) = E::split_filter_parts(&self.exclude);
+
+        let entities = match (get_entities, include_entities) {
+            (Some(get_entities), Some(include_entities)) => {
+                if get_entities.len() <= include_entities.len() {
+                    get_entities
+                } else {
+                    include_entities
+                }
+            }
+            (Some(get_entities), None) => get_entities,
+            (None, Some(include_entities)) => include_entities,
+            (None, None) => &[],
+        };
+
+        (entities, exclude_sparse, include_sparse, get_sparse, get_data)
+    }
+
     #[must_use]
     fn get_group_range(&self) -> Option<Range<usize>> {
         let get_info = self.get_info?;
@@ -1,6 +1,11 @@
 use crate::entity::Entity;
 use crate::query::Query;
 use crate::World;
+use alloc::vec::Vec;
+use core::iter;
+
+#[cfg(feature = "fixedbitset")]
+use fixedbitset::FixedBitSet;
 
 /// Queries one item that matches the query.
 #[must_use]
@@ -68,7 +73,7 @@ where
     }
 }
 
-impl<'a, G, I, E> QueryOne<'a, G, I, E>
+impl<G, I, E> QueryOne<'_, G, I, E>
 where
     G: Query,
     I: Query,
@@ -110,4 +115,101 @@ where
     {
         self.get(entity).map(f)
     }
+
+    /// Collects the items mapped to `entities` into `C`, in order.
+    ///
+    /// Returns the collected items along with the positions in `entities` that
+    /// did not match the query.
+    #[must_use]
+    pub fn gather<C>(&mut self, entities: &[Entity]) -> (C, Vec<usize>)
+    where
+        C: Default + for<'b> Extend<G::Item<'b>>,
+    {
+        let mut items = C::default();
+        let mut missing = Vec::new();
+
+        for (i, &entity) in entities.iter().enumerate() {
+            match self.get(entity) {
+                Some(item) => items.extend(iter::once(item)),
+                None => missing.push(i),
+            }
+        }
+
+        (items, missing)
+    }
+
+    /// Returns an iterator over the items mapped to `entities`, in order.
+    ///
+    /// Entities that don't match the query are skipped, and entities that
+    /// appear more than once are only yielded the first time.
+    pub fn iter_over<'b>(
+        &'b mut self,
+        entities: &'b [Entity],
+    ) -> impl Iterator<Item = G::Item<'b>> + 'b {
+        let (_, get_sparse, get_data) = G::split_sparse_parts(&self.get);
+        let (_, include_sparse) = I::split_filter_parts(&self.include);
+        let (_, exclude_sparse) = E::split_filter_parts(&self.exclude);
+        let world = self.world;
+        let mut yielded = Vec::<u64>::new();
+
+        entities.iter().filter_map(move |&entity| {
+            if !world.entities.contains(entity) {
+                return None;
+            }
+
+            let sparse = entity.sparse();
+            let (block, bit) = (sparse / 64, 1 << (sparse % 64));
+
+            if block >= yielded.len() {
+                yielded.resize(block + 1, 0);
+            }
+
+            // Yielding the same entity twice would alias mutable items.
+            if yielded[block] & bit != 0 {
+                return None;
+            }
+
+            if !E::contains_none_raw(exclude_sparse, sparse) {
+                return None;
+            }
+
+            if !I::contains_all_raw(include_sparse, sparse) {
+                return None;
+            }
+
+            let item = unsafe { G::get_sparse_raw(get_sparse, get_data, entity)? };
+            yielded[block] |= bit;
+            Some(item)
+        })
+    }
+
+    /// Returns an iterator over the items mapped to the entities whose indexes
+    /// are set in `set`, in ascending order of indexes.
+    ///
+    /// Indexes of entities that don't exist or don't match the query are
+    /// skipped.
+    #[cfg(feature = "fixedbitset")]
+    pub fn iter_in_set<'b>(
+        &'b mut self,
+        set: &'b FixedBitSet,
+    ) -> impl Iterator<Item = G::Item<'b>> + 'b {
+        let (_, get_sparse, get_data) = G::split_sparse_parts(&self.get);
+        let (_, include_sparse) = I::split_filter_parts(&self.include);
+        let (_, exclude_sparse) = E::split_filter_parts(&self.exclude);
+        let world = self.world;
+
+        set.ones().filter_map(move |sparse| {
+            let entity = world.entities.get_by_index(sparse)?;
+
+            if !E::contains_none_raw(exclude_sparse, sparse) {
+                return None;
+            }
+
+            if !I::contains_all_raw(include_sparse, sparse) {
+                return None;
+            }
+
+            unsafe { G::get_sparse_raw(get_sparse, get_data, entity) }
+        })
+    }
 }
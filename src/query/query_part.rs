@@ -1,4 +1,4 @@
-use crate::component::{Component, View, ViewGroupInfo, ViewMut};
+use crate::component::{Component, ComponentData, View, ViewGroupInfo, ViewMut};
 use crate::entity::{Entity, SparseVec};
 use crate::World;
 use core::ops::Range;
@@ -30,6 +30,10 @@ pub unsafe trait QueryPart {
     /// Data used for sparse and dense iteration.
     type Data<'a>: Copy;
 
+    /// The type data of the component whose grouping affects the view, if any.
+    /// Only used for diagnostics.
+    const GROUPED_COMPONENT: Option<ComponentData> = None;
+
     /// Borrows a view from the `world`.
     #[must_use]
     fn borrow(world: &World) -> Self::View<'_>;
@@ -193,6 +197,8 @@ where
     type SparseKey = usize;
     type Data<'a> = NonNull<T>;
 
+    const GROUPED_COMPONENT: Option<ComponentData> = Some(ComponentData::new::<T>());
+
     fn borrow(world: &World) -> Self::View<'_> {
         world.borrow::<T>()
     }
@@ -272,6 +278,8 @@ where
     type SparseKey = usize;
     type Data<'a> = NonNull<T>;
 
+    const GROUPED_COMPONENT: Option<ComponentData> = Some(ComponentData::new::<T>());
+
     fn borrow(world: &World) -> Self::View<'_> {
         world.borrow_mut::<T>()
     }
@@ -0,0 +1,105 @@
+use crate::component::ComponentSet;
+use crate::entity::Entity;
+use crate::world::World;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Deref;
+
+type Command = Box<dyn FnOnce(&mut World)>;
+
+/// Shared view over a [`World`] that queues structural changes, created with
+/// [`World::defer`].
+///
+/// Queries and borrows through the deferred world see the state of the world
+/// from before any queued change. The changes are applied in order when the
+/// scope ends.
+pub struct DeferredWorld<'a> {
+    world: &'a World,
+    commands: Vec<Command>,
+}
+
+impl<'a> DeferredWorld<'a> {
+    #[inline]
+    #[must_use]
+    pub(crate) fn new(world: &'a World) -> Self {
+        Self {
+            world,
+            commands: Vec::new(),
+        }
+    }
+
+    /// Queues the creation of an entity with the given `components`.
+    ///
+    /// Returns the entity to be created.
+    pub fn create<C>(&mut self, components: C) -> Entity
+    where
+        C: ComponentSet + 'static,
+    {
+        let entity = self.world.create_atomic();
+
+        self.commands.push(Box::new(move |world| {
+            world.insert(entity, components);
+        }));
+
+        entity
+    }
+
+    /// Queues the removal of `entity` and its associated components from the
+    /// world.
+    pub fn destroy(&mut self, entity: Entity) {
+        self.commands.push(Box::new(move |world| {
+            world.destroy(entity);
+        }));
+    }
+
+    /// Queues the insertion of `components` to `entity`.
+    pub fn insert<C>(&mut self, entity: Entity, components: C)
+    where
+        C: ComponentSet + 'static,
+    {
+        self.commands.push(Box::new(move |world| {
+            world.insert(entity, components);
+        }));
+    }
+
+    /// Queues the removal of the components in `C` from `entity`.
+    pub fn delete<C>(&mut self, entity: Entity)
+    where
+        C: ComponentSet,
+    {
+        self.commands.push(Box::new(move |world| {
+            world.delete::<C>(entity);
+        }));
+    }
+
+    /// Returns the number of queued changes.
+    #[inline]
+    #[must_use]
+    pub fn queued_len(&self) -> usize {
+        self.commands.len()
+    }
+
+    #[must_use]
+    pub(crate) fn into_commands(self) -> Vec<Command> {
+        self.commands
+    }
+}
+
+impl Deref for DeferredWorld<'_> {
+    type Target = World;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.world
+    }
+}
+
+impl fmt::Debug for DeferredWorld<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(stringify!(DeferredWorld))
+            .field("world", &self.world)
+            .field("queued_len", &self.commands.len())
+            .finish()
+    }
+}
@@ -0,0 +1,105 @@
+use crate::component::{Component, ComponentRef, ComponentSet};
+use crate::entity::Entity;
+use crate::query::Query;
+use crate::world::World;
+use core::fmt;
+
+/// Exclusive handle to an entity in a [`World`], used for reading, adding and
+/// removing the components of the entity.
+#[must_use]
+pub struct EntityMut<'a> {
+    world: &'a mut World,
+    entity: Entity,
+}
+
+impl<'a> EntityMut<'a> {
+    #[inline]
+    pub(crate) fn new(world: &'a mut World, entity: Entity) -> Self {
+        Self { world, entity }
+    }
+
+    /// Inserts `components` to the entity, overwriting previous data if
+    /// necessary. The components are grouped immediately.
+    pub fn insert<C>(&mut self, components: C) -> &mut Self
+    where
+        C: ComponentSet,
+    {
+        self.world.insert(self.entity, components);
+        self
+    }
+
+    /// Removes components from the entity, returning the removed components
+    /// as options.
+    #[must_use = "Use `delete` to discard the components."]
+    pub fn remove<C>(&mut self) -> C::Remove
+    where
+        C: ComponentSet,
+    {
+        self.world.remove::<C>(self.entity)
+    }
+
+    /// Removes components from the entity, without returning them.
+    pub fn delete<C>(&mut self) -> &mut Self
+    where
+        C: ComponentSet,
+    {
+        self.world.delete::<C>(self.entity);
+        self
+    }
+
+    /// Returns a reference to the component of type `T` of the entity, if any.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is not registered.
+    #[must_use]
+    pub fn get<T>(&self) -> Option<ComponentRef<'_, T>>
+    where
+        T: Component,
+    {
+        self.world.components.get::<T>(self.entity)
+    }
+
+    /// Returns a mutable reference to the component of type `T` of the entity,
+    /// if any.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is not registered.
+    #[must_use]
+    pub fn get_mut<T>(&mut self) -> Option<&mut T>
+    where
+        T: Component,
+    {
+        unsafe {
+            self.world
+                .components
+                .sparse_set_mut::<T>()
+                .get_mut::<T>(self.entity)
+        }
+    }
+
+    /// Returns whether the entity matches the query.
+    #[must_use]
+    pub fn contains<G>(&self) -> bool
+    where
+        G: Query,
+    {
+        self.world.contains::<G>(self.entity)
+    }
+
+    /// Returns the entity.
+    #[inline]
+    #[must_use]
+    pub fn id(&self) -> Entity {
+        self.entity
+    }
+}
+
+impl fmt::Debug for EntityMut<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(stringify!(EntityMut))
+            .field("entity", &self.entity)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,58 @@
+use crate::component::{Component, ComponentRef};
+use crate::entity::Entity;
+use crate::query::Query;
+use crate::world::World;
+use core::fmt;
+
+/// Shared handle to an entity in a [`World`], used for reading the components
+/// of the entity.
+#[derive(Clone, Copy)]
+pub struct EntityRef<'a> {
+    world: &'a World,
+    entity: Entity,
+}
+
+impl<'a> EntityRef<'a> {
+    #[inline]
+    #[must_use]
+    pub(crate) fn new(world: &'a World, entity: Entity) -> Self {
+        Self { world, entity }
+    }
+
+    /// Returns a reference to the component of type `T` of the entity, if any.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is not registered or is mutably borrowed.
+    #[must_use]
+    pub fn get<T>(&self) -> Option<ComponentRef<'a, T>>
+    where
+        T: Component,
+    {
+        self.world.components.get::<T>(self.entity)
+    }
+
+    /// Returns whether the entity matches the query.
+    #[must_use]
+    pub fn contains<G>(&self) -> bool
+    where
+        G: Query,
+    {
+        self.world.contains::<G>(self.entity)
+    }
+
+    /// Returns the entity.
+    #[inline]
+    #[must_use]
+    pub fn id(&self) -> Entity {
+        self.entity
+    }
+}
+
+impl fmt::Debug for EntityRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(stringify!(EntityRef))
+            .field("entity", &self.entity)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,123 @@
+use crate::component::ComponentData;
+use core::error::Error;
+use core::fmt;
+
+/// Error returned when a component is not part of the group layout of a
+/// [`World`](crate::World).
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct NotInLayoutError {
+    component: ComponentData,
+}
+
+impl NotInLayoutError {
+    #[inline]
+    #[must_use]
+    pub(crate) const fn new(component: ComponentData) -> Self {
+        Self { component }
+    }
+
+    /// Returns the type data of the component that is not in the layout.
+    #[inline]
+    #[must_use]
+    pub const fn component(&self) -> ComponentData {
+        self.component
+    }
+}
+
+impl fmt::Display for NotInLayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Component '{}' is not part of the group layout",
+            self.component.type_name(),
+        )
+    }
+}
+
+impl Error for NotInLayoutError {
+    // Empty
+}
+
+/// Error returned when an operation that reorders a component storage is
+/// attempted on a grouped component, since that would break its groups.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct GroupedComponentError {
+    component: ComponentData,
+}
+
+impl GroupedComponentError {
+    #[inline]
+    #[must_use]
+    pub(crate) const fn new(component: ComponentData) -> Self {
+        Self { component }
+    }
+
+    /// Returns the type data of the grouped component.
+    #[inline]
+    #[must_use]
+    pub const fn component(&self) -> ComponentData {
+        self.component
+    }
+}
+
+impl fmt::Display for GroupedComponentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Component '{}' is grouped and its storage can't be reordered",
+            self.component.type_name(),
+        )
+    }
+}
+
+impl Error for GroupedComponentError {
+    // Empty
+}
+
+/// Error returned when registering a required component would create a cycle
+/// of requirements.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct RequiredComponentCycleError {
+    component: ComponentData,
+    required: ComponentData,
+}
+
+impl RequiredComponentCycleError {
+    #[inline]
+    #[must_use]
+    pub(crate) const fn new(component: ComponentData, required: ComponentData) -> Self {
+        Self {
+            component,
+            required,
+        }
+    }
+
+    /// Returns the type data of the requiring component.
+    #[inline]
+    #[must_use]
+    pub const fn component(&self) -> ComponentData {
+        self.component
+    }
+
+    /// Returns the type data of the required component.
+    #[inline]
+    #[must_use]
+    pub const fn required(&self) -> ComponentData {
+        self.required
+    }
+}
+
+impl fmt::Display for RequiredComponentCycleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Component '{}' can't require '{}' because it would create a cycle",
+            self.component.type_name(),
+            self.required.type_name(),
+        )
+    }
+}
+
+impl Error for RequiredComponentCycleError {
+    // Empty
+}
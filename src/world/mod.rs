@@ -1,21 +1,54 @@
 //! Manage and query entities and their associated components.
 
 mod builder;
+mod deferred_world;
+mod entity_mut;
+mod entity_ref;
+mod error;
 
 pub use self::builder::*;
+pub use self::deferred_world::*;
+pub use self::entity_mut::*;
+pub use self::entity_ref::*;
+pub use self::error::*;
 
 use crate::component::{
-    Component, ComponentData, ComponentSet, ComponentStorage, GroupInfo, GroupLayout, View, ViewMut,
+    Component, ComponentData, ComponentSet, ComponentStorage, EntitiesRef, GroupInfo, GroupLayout,
+    View, ViewMut,
 };
-use crate::entity::{Entity, EntityStorage};
+use crate::entity::{Entity, EntityStorage, Version};
 use crate::query::{Query, QueryAll, QueryOne};
-use core::any::TypeId;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::any::{self, TypeId};
+use core::cmp::Ordering;
+use core::fmt;
+use core::ptr::NonNull;
+use hashbrown::HashMap;
+use rustc_hash::FxBuildHasher;
+
+type IndexRetiredCallback = Box<dyn FnMut(u32) + Send + Sync>;
+type Template = Arc<dyn Fn(&mut World, Entity) + Send + Sync>;
+
+#[derive(Clone, Copy)]
+struct RequiredComponent {
+    component: ComponentData,
+    insert_default: fn(&mut World, Entity),
+}
 
 /// Collection for entities and their associated components.
-#[derive(Default, Debug)]
+#[derive(Default)]
 pub struct World {
     pub(crate) entities: EntityStorage,
     pub(crate) components: ComponentStorage,
+    on_index_retired: Option<IndexRetiredCallback>,
+    required: HashMap<TypeId, Vec<RequiredComponent>, FxBuildHasher>,
+    templates: HashMap<String, Template, FxBuildHasher>,
+    generation: u64,
 }
 
 impl World {
@@ -32,6 +65,10 @@ impl World {
         Self {
             entities: EntityStorage::default(),
             components: ComponentStorage::new(layout),
+            on_index_retired: None,
+            required: HashMap::default(),
+            templates: HashMap::default(),
+            generation: 0,
         }
     }
 
@@ -44,6 +81,37 @@ impl World {
         unsafe {
             self.components.set_layout(layout, self.entities.as_slice());
         }
+
+        self.generation += 1;
+    }
+
+    /// Builds a group layout from the given `groups` of registered component
+    /// types and sets it on this world, replacing the previous layout.
+    ///
+    /// This operation requires iteration over all entities in the world, so it
+    /// is best called when the world is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the component types is not registered, or if the
+    /// groups don't form a valid layout.
+    pub fn auto_group(&mut self, groups: &[&[TypeId]]) {
+        let mut layout = GroupLayout::default();
+
+        for &group in groups {
+            let components = group
+                .iter()
+                .map(|&type_id| {
+                    self.components
+                        .component_data(type_id)
+                        .unwrap_or_else(|| panic!("Component {type_id:?} was not registered"))
+                })
+                .collect::<Vec<_>>();
+
+            layout.add_group_dyn(&components);
+        }
+
+        self.set_layout(&layout);
     }
 
     /// Registers a new component type on this world.
@@ -61,7 +129,12 @@ impl World {
     /// Returns whether the component was newly registered.
     #[inline]
     pub fn register_dyn(&mut self, component: ComponentData) -> bool {
-        self.components.register_dyn(component)
+        if !self.components.register_dyn(component) {
+            return false;
+        }
+
+        self.generation += 1;
+        true
     }
 
     /// Returns whether the component type is registered.
@@ -80,6 +153,77 @@ impl World {
         self.components.is_registered_dyn(component)
     }
 
+    /// Assigns a `stable_id` to component type `T`. Unlike [`TypeId`]s,
+    /// stable ids stay the same across builds, so they can be used for
+    /// serialization.
+    ///
+    /// Returns whether the id was assigned, i.e. whether neither `T` nor
+    /// `stable_id` were already assigned to something else.
+    pub fn assign_component_id<T>(&mut self, stable_id: u16) -> bool
+    where
+        T: Component,
+    {
+        self.components
+            .assign_stable_id(TypeId::of::<T>(), stable_id)
+    }
+
+    /// Returns the stable id assigned to component type `T`, if any.
+    #[must_use]
+    pub fn component_id<T>(&self) -> Option<u16>
+    where
+        T: Component,
+    {
+        self.components.stable_ids.get(&TypeId::of::<T>()).copied()
+    }
+
+    /// Returns the component type that `stable_id` was assigned to, if any.
+    #[must_use]
+    pub fn component_type_for_id(&self, stable_id: u16) -> Option<TypeId> {
+        self.components.stable_id_types.get(&stable_id).copied()
+    }
+
+    /// Declares that component `R` is required by component `T`. Whenever `T`
+    /// is added to an entity with [`create`](Self::create),
+    /// [`extend`](Self::extend) or [`insert`](Self::insert), `R::default()` is
+    /// inserted as well if the entity doesn't already have an `R`.
+    ///
+    /// Requirements are transitive. Returns an error if `R` already requires
+    /// `T`, directly or indirectly, since that would create a cycle.
+    /// Otherwise, `T` and `R` are registered if they weren't already.
+    pub fn register_required<T, R>(&mut self) -> Result<(), RequiredComponentCycleError>
+    where
+        T: Component,
+        R: Component + Default,
+    {
+        let component = ComponentData::new::<T>();
+        let required = ComponentData::new::<R>();
+
+        if self.requires(required.type_id(), component.type_id()) {
+            return Err(RequiredComponentCycleError::new(component, required));
+        }
+
+        self.register_dyn(component);
+        self.register_dyn(required);
+
+        let requirements = self.required.entry(component.type_id()).or_default();
+
+        if requirements
+            .iter()
+            .all(|requirement| requirement.component != required)
+        {
+            requirements.push(RequiredComponent {
+                component: required,
+                insert_default: |world, entity| {
+                    if !world.borrow::<R>().contains(entity) {
+                        world.insert(entity, (R::default(),));
+                    }
+                },
+            });
+        }
+
+        Ok(())
+    }
+
     /// Creates a new entity with the given `components`.
     ///
     /// Returns the newly created entity.
@@ -88,14 +232,42 @@ impl World {
         C: ComponentSet,
     {
         let entity = self.entities.create();
+        self.generation += 1;
 
         unsafe {
             C::insert(self, entity, components);
         }
 
+        self.insert_required(entity, C::COMPONENTS);
         entity
     }
 
+    /// Creates a new entity without any components and returns a handle for
+    /// adding components to it.
+    pub fn spawn_empty(&mut self) -> EntityMut<'_> {
+        let entity = self.entities.create();
+        self.generation += 1;
+        EntityMut::new(self, entity)
+    }
+
+    /// Returns a shared handle to `entity`, if it exists in the world.
+    #[must_use]
+    pub fn entity(&self, entity: Entity) -> Option<EntityRef<'_>> {
+        self.entities
+            .contains(entity)
+            .then(|| EntityRef::new(self, entity))
+    }
+
+    /// Returns an exclusive handle to `entity`, if it exists in the world.
+    #[must_use]
+    pub fn entity_mut(&mut self, entity: Entity) -> Option<EntityMut<'_>> {
+        if !self.entities.contains(entity) {
+            return None;
+        }
+
+        Some(EntityMut::new(self, entity))
+    }
+
     /// Creates new entities with the `components` produced by the iterator.
     ///
     /// Returns the newly created entities as a slice.
@@ -104,7 +276,164 @@ impl World {
         C: ComponentSet,
         I: IntoIterator<Item = C>,
     {
-        C::extend(self, components)
+        self.extend_with_required(components)
+    }
+
+    /// Creates new entities with the `components` produced by the iterator,
+    /// calling `f` with each new entity and its components before they are
+    /// added to the world.
+    ///
+    /// Returns the values returned by `f`, in iteration order.
+    pub fn extend_mapped<C, I, F, R>(&mut self, components: I, mut f: F) -> Vec<R>
+    where
+        C: ComponentSet,
+        I: IntoIterator<Item = C>,
+        F: FnMut(Entity, &C) -> R,
+    {
+        let components = components.into_iter();
+        let mut results = Vec::with_capacity(components.size_hint().0);
+        self.generation += 1;
+
+        for components in components {
+            let entity = self.entities.create();
+            results.push(f(entity, &components));
+
+            unsafe {
+                C::insert(self, entity, components);
+            }
+
+            self.insert_required(entity, C::COMPONENTS);
+        }
+
+        results
+    }
+
+    /// Creates new entities with the `components` produced by the iterator,
+    /// requiring all components to be part of the group layout.
+    ///
+    /// Returns the newly created entities as a slice, or an error if any
+    /// component is not part of the group layout, in which case no entities
+    /// are created. Only the components reported by
+    /// [`ComponentSet::COMPONENTS`] are checked.
+    pub fn extend_strict<C, I>(&mut self, components: I) -> Result<&[Entity], NotInLayoutError>
+    where
+        C: ComponentSet,
+        I: IntoIterator<Item = C>,
+    {
+        if let Some(&component) = C::COMPONENTS
+            .iter()
+            .find(|component| !self.components.is_grouped_dyn(component.type_id()))
+        {
+            return Err(NotInLayoutError::new(component));
+        }
+
+        Ok(self.extend_with_required(components))
+    }
+
+    /// Registers a template under the given `name`. The `template` is run
+    /// against each entity created with [`spawn_template`](Self::spawn_template)
+    /// and is typically used to insert the entity's initial components.
+    ///
+    /// Returns whether the template was newly registered. If a template with
+    /// the same name already exists, it is replaced.
+    pub fn register_template<F>(&mut self, name: impl Into<String>, template: F) -> bool
+    where
+        F: Fn(&mut World, Entity) + Send + Sync + 'static,
+    {
+        self.templates
+            .insert(name.into(), Arc::new(template))
+            .is_none()
+    }
+
+    /// Creates a new entity and runs the template registered under `name`
+    /// against it.
+    ///
+    /// Returns the newly created entity, or [`None`] if no template with the
+    /// given name exists, in which case no entity is created.
+    pub fn spawn_template(&mut self, name: &str) -> Option<Entity> {
+        let template = self.templates.get(name)?.clone();
+        let entity = self.entities.create();
+        self.generation += 1;
+        template(self, entity);
+        Some(entity)
+    }
+
+    /// Moves every entity in the world to the index returned by `mapping`,
+    /// keeping its version and components.
+    ///
+    /// The mapping is called once for the index of each entity in the world,
+    /// after [`maintain`](Self::maintain)ing it. Entities can be moved to the
+    /// index of another entity that is also moved, or to a free index, which
+    /// makes it possible to both reorder and compact entities. Indices that
+    /// are left without an entity are freed for new entities, with their
+    /// versions bumped as if their entities were destroyed.
+    ///
+    /// Components that store entities must be updated by the caller, for
+    /// example by applying the same mapping through
+    /// [`borrow_mut`](Self::borrow_mut). Handles to entities created before
+    /// remapping may refer to different entities afterwards.
+    ///
+    /// # Panics
+    ///
+    /// Panics if two entities are mapped to the same index, or if an entity is
+    /// mapped to an index that belongs to an entity that isn't moved, or that
+    /// can't be reused until the world is [`reset`](Self::reset).
+    pub fn remap_indices<F>(&mut self, mut mapping: F)
+    where
+        F: FnMut(u32) -> u32,
+    {
+        self.maintain();
+
+        let entities = self.entities.as_slice();
+        let is_free = self.entities.free_mask();
+
+        let mut is_alive = vec![false; is_free.len()];
+        let mut new_indices = vec![0; is_free.len()];
+
+        for entity in entities {
+            is_alive[entity.sparse()] = true;
+        }
+
+        for entity in entities {
+            let new_index = mapping(entity.index);
+            let i = new_index as usize;
+
+            assert!(
+                i >= is_free.len() || is_free[i] || is_alive[i],
+                "Index {new_index} is neither the index of an entity in the world nor free",
+            );
+
+            new_indices[entity.sparse()] = new_index;
+        }
+
+        let mut occupied = entities
+            .iter()
+            .map(|entity| new_indices[entity.sparse()])
+            .collect::<Vec<_>>();
+
+        occupied.sort_unstable();
+
+        if let Some(&[index, _]) = occupied.windows(2).find(|indices| indices[0] == indices[1]) {
+            panic!("Index {index} is mapped more than once");
+        }
+
+        let vacated = entities
+            .iter()
+            .filter(|entity| occupied.binary_search(&entity.index).is_err())
+            .copied()
+            .collect::<Vec<_>>();
+
+        let remap = |entity: Entity| Entity::new(new_indices[entity.sparse()], entity.version);
+        self.entities.remap(remap, &occupied, &vacated);
+        self.components.remap(remap);
+        self.generation += 1;
+
+        if let Some(on_index_retired) = &mut self.on_index_retired {
+            vacated
+                .iter()
+                .filter(|entity| entity.version == Version::LAST)
+                .for_each(|entity| on_index_retired(entity.index));
+        }
     }
 
     /// Removes the `entity` and its associated components from the world.
@@ -118,9 +447,86 @@ impl World {
         }
 
         self.components.strip(entity);
+        self.generation += 1;
+
+        if entity.version == Version::LAST {
+            if let Some(on_index_retired) = &mut self.on_index_retired {
+                on_index_retired(entity.index);
+            }
+        }
+
         true
     }
 
+    /// Destroys all entities with a component of type `T`, along with their
+    /// other components.
+    ///
+    /// Returns the number of destroyed entities. Returns 0 if `T` is not
+    /// registered.
+    pub fn despawn_with<T>(&mut self) -> usize
+    where
+        T: Component,
+    {
+        let Some(entities) = self.components.entities_with::<T>().map(|e| e.to_vec()) else {
+            return 0;
+        };
+
+        for &entity in &entities {
+            self.destroy(entity);
+        }
+
+        entities.len()
+    }
+
+    /// Sets a `callback` to be invoked with the index of each destroyed entity
+    /// whose version was exhausted, meaning the index can no longer be reused
+    /// until the world is [`reset`](Self::reset).
+    #[inline]
+    pub fn on_index_retired<F>(&mut self, callback: F)
+    where
+        F: FnMut(u32) + Send + Sync + 'static,
+    {
+        self.on_index_retired = Some(Box::new(callback));
+    }
+
+    /// Sets a `callback` to be invoked whenever a component of type `T` is
+    /// inserted, including when it overwrites a previous component.
+    ///
+    /// The `callback` receives the entity and the inserted component. It has
+    /// no access to the world, so it can't modify the storage mid-operation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is not registered.
+    pub fn on_insert<T, F>(&mut self, mut callback: F)
+    where
+        T: Component,
+        F: FnMut(Entity, &T) + Send + Sync + 'static,
+    {
+        self.components
+            .sparse_set_mut::<T>()
+            .set_on_insert(Box::new(move |entity, component| {
+                callback(entity, unsafe { component.cast::<T>().as_ref() });
+            }));
+    }
+
+    /// Sets a `callback` to be invoked whenever a component of type `T` is
+    /// removed from an entity, including when the entity is destroyed or the
+    /// world is cleared.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is not registered.
+    pub fn on_remove<T, F>(&mut self, callback: F)
+    where
+        T: Component,
+        F: FnMut(Entity) + Send + Sync + 'static,
+    {
+        self.components
+            .sparse_set_mut::<T>()
+            .set_on_remove(Box::new(callback));
+    }
+
     /// Queues the creation of an entity without requiring exclusive access to
     /// the world. Entities created with this method can be added to the world
     /// by calling [`maintain`](Self::maintain).
@@ -136,6 +542,25 @@ impl World {
     #[inline]
     pub fn maintain(&mut self) {
         self.entities.maintain();
+        self.generation += 1;
+    }
+
+    /// Calls `f` with a [`DeferredWorld`] that queues structural changes while
+    /// still allowing queries, then applies the queued changes in order.
+    ///
+    /// Returns the result of `f`.
+    pub fn defer<R>(&mut self, f: impl FnOnce(&mut DeferredWorld) -> R) -> R {
+        let mut deferred = DeferredWorld::new(self);
+        let result = f(&mut deferred);
+        let commands = deferred.into_commands();
+
+        self.maintain();
+
+        for command in commands {
+            command(self);
+        }
+
+        result
     }
 
     /// Inserts `components` to an existing `entity`, overwriting previous data
@@ -151,13 +576,52 @@ impl World {
             return false;
         }
 
+        self.generation += 1;
+
         unsafe {
             C::insert(self, entity, components);
         }
 
+        self.insert_required(entity, C::COMPONENTS);
         true
     }
 
+    /// Inserts `components` to an existing `entity` only if it has none of
+    /// them.
+    ///
+    /// Returns whether the `components` were added, i.e. whether the `entity`
+    /// existed in the world and had none of the components before this call.
+    pub fn insert_if_absent<C>(&mut self, entity: Entity, components: C) -> bool
+    where
+        C: ComponentSet,
+    {
+        if C::COMPONENTS.iter().any(|component| {
+            self.components
+                .get_raw(component.type_id(), entity)
+                .is_some()
+        }) {
+            return false;
+        }
+
+        self.insert(entity, components)
+    }
+
+    /// Clones the component of type `T` from entity `from` into entity `to`,
+    /// overwriting previous data if necessary.
+    ///
+    /// Returns whether the component was copied successfully, i.e. whether
+    /// `from` has a component of type `T` and `to` exists in the world.
+    pub fn copy_component<T>(&mut self, from: Entity, to: Entity) -> bool
+    where
+        T: Component + Clone,
+    {
+        let Some(component) = self.borrow::<T>().get(from).cloned() else {
+            return false;
+        };
+
+        self.insert(to, (component,))
+    }
+
     /// Removes components from the `entity`, returning the removed components
     /// as options.
     #[must_use = "Use `delete` to discard the components."]
@@ -165,6 +629,7 @@ impl World {
     where
         C: ComponentSet,
     {
+        self.generation += 1;
         unsafe { C::remove(self, entity) }
     }
 
@@ -175,13 +640,185 @@ impl World {
     where
         C: ComponentSet,
     {
+        self.generation += 1;
+
         unsafe {
             C::delete(self, entity);
         }
     }
 
+    /// Removes the components of type `T` from each of the `entities`,
+    /// returning the removed components in the same order.
+    ///
+    /// Entities that don't exist or don't have the component yield `None`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is not registered.
+    #[must_use = "Use `delete` to discard the components."]
+    pub fn remove_component_many<T>(&mut self, entities: &[Entity]) -> Vec<Option<T>>
+    where
+        T: Component,
+    {
+        self.generation += 1;
+        self.components.remove_many::<T>(entities)
+    }
+
+    /// Removes all components of type `T` from the world, dropping them. The
+    /// entities remain in the world and `T` stays registered.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is not registered.
+    pub fn clear_component<T>(&mut self)
+    where
+        T: Component,
+    {
+        self.components.clear_component::<T>();
+        self.generation += 1;
+    }
+
+    /// Removes all components of type `T` from the world, returning them
+    /// along with the entities that owned them.
+    ///
+    /// The entities remain in the world.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is not registered.
+    pub fn drain_component<T>(&mut self) -> impl Iterator<Item = (Entity, T)>
+    where
+        T: Component,
+    {
+        self.generation += 1;
+        self.components.drain::<T>().into_iter()
+    }
+
+    /// Replaces all components of type `T` in the world with the given
+    /// `components`.
+    ///
+    /// Entities that had a component of type `T` but are missing from
+    /// `components` lose it. Entities that don't exist in the world are
+    /// skipped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is not registered.
+    pub fn replace_storage<T>(&mut self, components: Vec<(Entity, T)>)
+    where
+        T: Component,
+    {
+        self.components.clear_component::<T>();
+        self.generation += 1;
+
+        for (entity, component) in components {
+            self.insert(entity, (component,));
+        }
+    }
+
+    /// Sorts the storage of component type `T` with the `compare` function, so
+    /// that iterating it visits the components in sorted order.
+    ///
+    /// Returns an error if `T` is grouped, since sorting it would break its
+    /// groups.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is not registered.
+    pub fn sort_component<T, F>(&mut self, compare: F) -> Result<(), GroupedComponentError>
+    where
+        T: Component,
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        if self.components.is_grouped_dyn(TypeId::of::<T>()) {
+            return Err(GroupedComponentError::new(ComponentData::new::<T>()));
+        }
+
+        unsafe {
+            self.components.sparse_set_mut::<T>().sort_by(compare);
+        }
+
+        Ok(())
+    }
+
+    /// Swaps the components of type `T` at dense indexes `a` and `b`, along
+    /// with the entities that own them. Lookups by entity are unaffected.
+    ///
+    /// Returns an error if `T` is grouped, since reordering it would break its
+    /// groups.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is not registered or if `a` or `b` are out of bounds.
+    pub fn swap_dense_positions<T>(
+        &mut self,
+        a: usize,
+        b: usize,
+    ) -> Result<(), GroupedComponentError>
+    where
+        T: Component,
+    {
+        if self.components.is_grouped_dyn(TypeId::of::<T>()) {
+            return Err(GroupedComponentError::new(ComponentData::new::<T>()));
+        }
+
+        self.components.sparse_set_mut::<T>().swap_dense(a, b);
+        Ok(())
+    }
+
+    /// Shortens the storage of component `T` to the first `len` components in
+    /// dense order. The entities owning the removed components keep existing
+    /// but no longer have a `T`. Has no effect if `len` is greater than or
+    /// equal to the number of components.
+    ///
+    /// Returns an error if `T` is grouped, since truncation would break its
+    /// groups.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is not registered.
+    pub fn truncate_component<T>(&mut self, len: usize) -> Result<(), GroupedComponentError>
+    where
+        T: Component,
+    {
+        if self.components.is_grouped_dyn(TypeId::of::<T>()) {
+            return Err(GroupedComponentError::new(ComponentData::new::<T>()));
+        }
+
+        unsafe {
+            self.components.sparse_set_mut::<T>().truncate::<T>(len);
+        }
+
+        self.generation += 1;
+        Ok(())
+    }
+
+    /// Clears the entity references of all components of type `T` that point
+    /// to entities no longer in the world.
+    ///
+    /// The `extract` function returns the entity reference of a component.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is not registered.
+    pub fn prune_entity_refs<T, F>(&mut self, mut extract: F)
+    where
+        T: Component,
+        F: FnMut(&mut T) -> &mut Option<Entity>,
+    {
+        let components = unsafe { self.components.sparse_set_mut::<T>().as_mut_slice::<T>() };
+
+        for component in components {
+            let target = extract(component);
+
+            if target.is_some_and(|entity| !self.entities.contains(entity)) {
+                *target = None;
+            }
+        }
+    }
+
     /// Queries an entity with the given components.
-    pub fn query_one<G>(&self) -> QueryOne<G, (), ()>
+    pub fn query_one<G>(&self) -> QueryOne<'_, G, (), ()>
     where
         G: Query,
     {
@@ -189,13 +826,51 @@ impl World {
     }
 
     /// Queries all entities with the given components.
-    pub fn query_all<G>(&self) -> QueryAll<G, (), ()>
+    pub fn query_all<G>(&self) -> QueryAll<'_, G, (), ()>
     where
         G: Query,
     {
         QueryAll::new(self)
     }
 
+    /// Returns whether a query over the given components would iterate densely
+    /// under the current group layout.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the components are mutably borrowed.
+    #[must_use]
+    pub fn query_is_dense<G>(&self) -> bool
+    where
+        G: Query,
+    {
+        self.query_all::<G>().is_dense()
+    }
+
+    /// Asserts that a query over the given components would iterate densely
+    /// under the current group layout. Does nothing in release builds.
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if the query would iterate sparsely, naming the
+    /// components that prevent it from being grouped when the query reports
+    /// them through [`Query::GROUPED_COMPONENTS`], or if any of the components
+    /// are mutably borrowed.
+    #[track_caller]
+    pub fn assert_grouped<G>(&self)
+    where
+        G: Query,
+    {
+        if cfg!(debug_assertions) {
+            assert!(
+                self.query_is_dense::<G>(),
+                "Query '{}' is not grouped: {}",
+                any::type_name::<G>(),
+                self.describe_ungrouped(G::GROUPED_COMPONENTS),
+            );
+        }
+    }
+
     /// Returns whether the `entity` contains the given components.
     #[must_use]
     pub fn contains<G>(&self, entity: Entity) -> bool
@@ -205,6 +880,19 @@ impl World {
         QueryOne::<G, (), ()>::new(self).contains(entity)
     }
 
+    /// Collects the components of `entities` into `C`, in order.
+    ///
+    /// Returns the collected components along with the positions in
+    /// `entities` that did not match the query.
+    #[must_use]
+    pub fn gather<G, C>(&self, entities: &[Entity]) -> (C, Vec<usize>)
+    where
+        G: Query,
+        C: Default + for<'a> Extend<G::Item<'a>>,
+    {
+        QueryOne::<G, (), ()>::new(self).gather(entities)
+    }
+
     /// Iterates over all entities with the given components.
     pub fn for_each<G>(&self, f: impl FnMut(G::Item<'_>))
     where
@@ -236,6 +924,156 @@ impl World {
         self.entities.as_slice()
     }
 
+    /// Returns how many times each entity index was recycled, indexed by
+    /// entity index. This is the current version of the index minus one, so a
+    /// freshly allocated index reports 0.
+    ///
+    /// Indices that can't be reused until the world is [`reset`](Self::reset),
+    /// either because their versions were exhausted or because the world was
+    /// [`clear`](Self::clear)ed, report `u32::MAX`.
+    #[must_use]
+    pub fn index_reuse_histogram(&self) -> Vec<u32> {
+        self.entities.reuse_counts()
+    }
+
+    /// Returns all entities with a component of type `T` as a slice, or
+    /// [`None`] if `T` is not registered.
+    ///
+    /// The components of type `T` stay borrowed while the returned reference
+    /// is alive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is mutably borrowed.
+    #[must_use]
+    pub fn entities_with<T>(&self) -> Option<EntitiesRef<'_>>
+    where
+        T: Component,
+    {
+        self.components.entities_with::<T>()
+    }
+
+    /// Returns an iterator over the entities in the world that don't have a
+    /// component of type `T`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is not registered or is mutably borrowed.
+    pub fn entities_without<T>(&self) -> impl Iterator<Item = Entity> + '_
+    where
+        T: Component,
+    {
+        let view = self.borrow::<T>();
+
+        self.entities()
+            .iter()
+            .copied()
+            .filter(move |&entity| !view.contains(entity))
+    }
+
+    /// Clones all components of type `T` along with the entities that own them
+    /// into a vector that doesn't borrow the world.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is not registered or is mutably borrowed.
+    #[must_use]
+    pub fn snapshot_component<T>(&self) -> Vec<(Entity, T)>
+    where
+        T: Component + Clone,
+    {
+        let view = self.borrow::<T>();
+        let (entities, components) = view.as_slices();
+
+        entities
+            .iter()
+            .copied()
+            .zip(components.iter().cloned())
+            .collect()
+    }
+
+    /// Returns a type-erased pointer to the component of type `type_id` mapped
+    /// to `entity`, if any.
+    ///
+    /// The pointer is invalidated when components of the same type are added
+    /// or removed, or when the storage is reordered. Reading or writing through
+    /// it while a conflicting view of the component is alive is undefined
+    /// behavior.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the component is mutably borrowed.
+    #[must_use]
+    pub fn get_component_raw(&self, type_id: TypeId, entity: Entity) -> Option<*mut u8> {
+        self.components
+            .get_raw(type_id, entity)
+            .map(NonNull::as_ptr)
+    }
+
+    /// Returns the dense array of components of type `type_id` as a base
+    /// pointer, the number of components and the size of each component in
+    /// bytes.
+    ///
+    /// The component at index `i` starts at `ptr + i * size`, and is mapped to
+    /// the entity at the same index in [`entities_with`](Self::entities_with).
+    /// For zero-sized components the pointer is dangling and must not be read.
+    ///
+    /// The triple is invalidated when the
+    /// [structural generation](Self::component_structural_generation) of the
+    /// storage changes, as components may have been added, removed, reordered
+    /// or reallocated. Reading through the pointer while a mutable view of the
+    /// component is alive, or writing through it while any view of the
+    /// component is alive, is undefined behavior.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the component is mutably borrowed.
+    #[must_use]
+    pub fn component_slice_raw(&self, type_id: TypeId) -> Option<(NonNull<u8>, usize, usize)> {
+        self.components.slice_raw(type_id)
+    }
+
+    /// Returns the structural generation of the storage of components of type
+    /// `type_id`, if registered.
+    ///
+    /// The generation changes whenever components are added, removed or
+    /// reordered, but not when they are modified in place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the component is mutably borrowed.
+    #[must_use]
+    pub fn component_structural_generation(&self, type_id: TypeId) -> Option<u64> {
+        self.components.generation(type_id)
+    }
+
+    /// Returns the structural generation of the world, which changes whenever
+    /// entities are created or destroyed, components are added or removed,
+    /// component types are registered or the group layout is changed.
+    ///
+    /// Modifying components in place or reordering their storages doesn't
+    /// change the generation. It may also change after operations that turn
+    /// out to have no effect.
+    #[inline]
+    #[must_use]
+    pub fn structural_generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Checks the internal consistency of all component storages and groups,
+    /// returning a description of the first problem found.
+    ///
+    /// This is meant for debugging and iterates over all components in the
+    /// world.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any component is mutably borrowed.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        self.components
+            .check_invariants(|entity| self.entities.contains(entity))
+    }
+
     /// Returns whether the world contains no entities.
     #[inline]
     #[must_use]
@@ -248,6 +1086,7 @@ impl World {
     pub fn clear(&mut self) {
         self.entities.clear();
         self.components.clear();
+        self.generation += 1;
     }
 
     /// Removes all entities and components from the world and resets the entity
@@ -256,11 +1095,12 @@ impl World {
     pub fn reset(&mut self) {
         self.entities.reset();
         self.components.clear();
+        self.generation += 1;
     }
 
     /// Returns a shared view over all components of type `T`.
     #[must_use]
-    pub fn borrow<T>(&self) -> View<T>
+    pub fn borrow<T>(&self) -> View<'_, T>
     where
         T: Component,
     {
@@ -269,17 +1109,40 @@ impl World {
 
     /// Returns an exclusive view over all components of type `T`.
     #[must_use]
-    pub fn borrow_mut<T>(&self) -> ViewMut<T>
+    pub fn borrow_mut<T>(&self) -> ViewMut<'_, T>
     where
         T: Component,
     {
         self.components.borrow_mut::<T>()
     }
 
+    /// Calls `f` with a mutable reference to the component of type `T` mapped
+    /// to `entity` and a reference to the world, which can be used to query
+    /// other components while `T` is being modified.
+    ///
+    /// Returns the result of `f`, or [`None`] if `entity` has no `T`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is not registered or is already borrowed. Borrowing or
+    /// querying `T` through the world inside `f` also panics.
+    pub fn with_component_mut<T, R>(
+        &self,
+        entity: Entity,
+        f: impl FnOnce(&mut T, &World) -> R,
+    ) -> Option<R>
+    where
+        T: Component,
+    {
+        let mut view = self.borrow_mut::<T>();
+        let component = view.get_mut(entity)?;
+        Some(f(component, self))
+    }
+
     /// Returns a shared view over all components of type `T`, along with
     /// grouping information.
     #[must_use]
-    pub fn borrow_with_group_info<T>(&self) -> (View<T>, Option<GroupInfo>)
+    pub fn borrow_with_group_info<T>(&self) -> (View<'_, T>, Option<GroupInfo>)
     where
         T: Component,
     {
@@ -289,10 +1152,173 @@ impl World {
     /// Returns an exclusive view over all components of type `T`, along with
     /// grouping information.
     #[must_use]
-    pub fn borrow_with_group_info_mut<T>(&self) -> (ViewMut<T>, Option<GroupInfo>)
+    pub fn borrow_with_group_info_mut<T>(&self) -> (ViewMut<'_, T>, Option<GroupInfo>)
     where
         T: Component,
     {
         self.components.borrow_with_group_info_mut::<T>()
     }
+
+    #[must_use]
+    fn requires(&self, component: TypeId, required: TypeId) -> bool {
+        component == required
+            || self.required.get(&component).is_some_and(|requirements| {
+                requirements
+                    .iter()
+                    .any(|requirement| self.requires(requirement.component.type_id(), required))
+            })
+    }
+
+    fn insert_required(&mut self, entity: Entity, components: &[ComponentData]) {
+        if self.required.is_empty() {
+            return;
+        }
+
+        for component in components {
+            let Some(requirements) = self.required.get(&component.type_id()) else {
+                continue;
+            };
+
+            for requirement in requirements.clone() {
+                (requirement.insert_default)(self, entity);
+            }
+        }
+    }
+
+    #[cold]
+    fn describe_ungrouped(&self, components: &[Option<ComponentData>]) -> String {
+        let components = components
+            .iter()
+            .flatten()
+            .map(|component| {
+                let info = self.components.group_info(component.type_id());
+                (component.type_name(), info)
+            })
+            .collect::<Vec<_>>();
+
+        for (i, &(name, info)) in components.iter().enumerate() {
+            for &(other_name, other_info) in &components[..i] {
+                let is_grouped = info
+                    .zip(other_info)
+                    .is_some_and(|(info, other_info)| info.add_group(other_info).is_some());
+
+                if !is_grouped {
+                    return format!("component `{name}` is not in a group with `{other_name}`");
+                }
+            }
+        }
+
+        if components.is_empty() {
+            return String::from("the query doesn't report which components it groups");
+        }
+
+        let names = components
+            .iter()
+            .map(|(name, _)| format!("`{name}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!("no group contains exactly the components {names}")
+    }
+
+    fn extend_with_required<C, I>(&mut self, components: I) -> &[Entity]
+    where
+        C: ComponentSet,
+        I: IntoIterator<Item = C>,
+    {
+        let start = self.entities.len();
+        let end = C::extend(self, components).len() + start;
+        self.generation += 1;
+
+        if !self.required.is_empty() {
+            for i in start..end {
+                let entity = self.entities.as_slice()[i];
+                self.insert_required(entity, C::COMPONENTS);
+            }
+        }
+
+        &self.entities.as_slice()[start..end]
+    }
+}
+
+impl fmt::Debug for World {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(stringify!(World))
+            .field("entities", &self.entities)
+            .field("components", &self.components)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+
+    #[test]
+    fn test_index_retired_on_last_version() {
+        let retired_index = Arc::new(AtomicU32::new(u32::MAX));
+        let retired_count = Arc::new(AtomicUsize::new(0));
+
+        let mut world = World::default();
+        world.on_index_retired({
+            let retired_index = retired_index.clone();
+            let retired_count = retired_count.clone();
+
+            move |index| {
+                retired_index.store(index, Ordering::Relaxed);
+                retired_count.fetch_add(1, Ordering::Relaxed);
+            }
+        });
+
+        let e0 = world.create(());
+        let e1 = world.entities.create_with_version(Version::LAST);
+        let e2 = world.create(());
+        assert_eq!(e1.version, Version::LAST);
+        assert!(world.contains_entity(e1));
+
+        assert!(world.destroy(e1));
+        assert_eq!(retired_index.load(Ordering::Relaxed), e1.index);
+        assert_eq!(retired_count.load(Ordering::Relaxed), 1);
+
+        // Destroying the entity again doesn't retire the index twice
+        assert!(!world.destroy(e1));
+        assert_eq!(retired_count.load(Ordering::Relaxed), 1);
+
+        // The retired index is never allocated again
+        world.destroy(e0);
+        world.destroy(e2);
+        world.maintain();
+
+        for _ in 0..10 {
+            let entity = world.create(());
+            assert_ne!(entity.index, e1.index);
+        }
+
+        let entity = world.create_atomic();
+        assert_ne!(entity.index, e1.index);
+        world.maintain();
+
+        assert_eq!(world.index_reuse_histogram()[e1.sparse()], u32::MAX);
+        assert_eq!(retired_count.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_index_retired_on_remap() {
+        let retired_index = Arc::new(AtomicU32::new(u32::MAX));
+
+        let mut world = World::default();
+        world.on_index_retired({
+            let retired_index = retired_index.clone();
+            move |index| retired_index.store(index, Ordering::Relaxed)
+        });
+
+        let e0 = world.entities.create_with_version(Version::LAST);
+        world.remap_indices(|_| 1);
+        assert!(world.contains_entity(Entity::new(1, Version::LAST)));
+        assert_eq!(retired_index.load(Ordering::Relaxed), e0.index);
+
+        // The retired index is not freed
+        assert_eq!(world.create(()).index, 2);
+    }
 }
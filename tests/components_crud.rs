@@ -1,7 +1,14 @@
+//! Tests for inserting, reading and removing components.
+
 mod common;
 
 use common::*;
-use sparsey::World;
+use core::ptr::NonNull;
+use core::{mem, slice};
+use sparsey::component::ComponentData;
+use sparsey::{Entity, World};
+use std::any::TypeId;
+use std::sync::Arc;
 
 #[test]
 fn test_components_crud() {
@@ -51,3 +58,596 @@ fn test_components_crud() {
     assert_eq!(world.query_one::<&A>().get(e0), None);
     assert_eq!(world.query_one::<&B>().get(e0), None);
 }
+
+#[test]
+fn test_insert_if_absent() {
+    let mut world = World::builder()
+        .add_group::<(A, B)>()
+        .register::<C>()
+        .build();
+
+    let e0 = world.create((B(0),));
+    let e1 = world.create(());
+
+    // The entity already has one component of the set
+    assert!(!world.insert_if_absent(e0, (A(0), B(1), C(0))));
+    assert_eq!(world.borrow::<B>().get(e0), Some(&B(0)));
+    assert!(!world.contains::<&A>(e0));
+    assert!(!world.contains::<&C>(e0));
+
+    // The entity has none of the components
+    assert!(world.insert_if_absent(e1, (A(1), B(1))));
+    assert!(world.contains::<(&A, &B)>(e1));
+    assert_eq!(
+        world.query_all::<(&A, &B)>().slice().map(|s| s.0.len()),
+        Some(1)
+    );
+
+    // Repeated calls are no-ops
+    assert!(!world.insert_if_absent(e1, (A(2), B(2))));
+    assert_eq!(world.query_one::<(&A, &B)>().get(e1), Some((&A(1), &B(1))));
+
+    // Missing entities
+    world.destroy(e1);
+    assert!(!world.insert_if_absent(e1, (C(1),)));
+}
+
+#[test]
+fn test_copy_component() {
+    let mut world = World::builder().add_group::<(A, B)>().build();
+
+    let e0 = world.create((A(0), B(0)));
+    let e1 = world.create((B(1),));
+    let e2 = world.create((A(2), B(2)));
+    let e3 = world.create(());
+
+    // Copy to an entity without the component, completing its group
+    assert!(world.copy_component::<A>(e0, e1));
+    assert_eq!(world.query_one::<&A>().get(e1), Some(&A(0)));
+    assert_eq!(
+        world.query_all::<(&A, &B)>().slice().map(|(a, _)| a.len()),
+        Some(3)
+    );
+
+    // Copy over an existing component
+    assert!(world.copy_component::<A>(e2, e0));
+    assert_eq!(world.query_one::<&A>().get(e0), Some(&A(2)));
+    assert_eq!(world.query_one::<&A>().get(e2), Some(&A(2)));
+
+    // Source without the component
+    assert!(!world.copy_component::<A>(e3, e0));
+    assert_eq!(world.query_one::<&A>().get(e0), Some(&A(2)));
+
+    // Destroyed destination
+    world.destroy(e3);
+    assert!(!world.copy_component::<A>(e0, e3));
+    assert!(!world.contains::<&A>(e3));
+}
+
+#[test]
+fn test_zero_sized_component() {
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    #[repr(align(16))]
+    struct Tag;
+
+    let mut world = World::builder().register::<A>().register::<Tag>().build();
+    let e0 = world.create((A(0), Tag));
+    let e1 = world.create((A(1),));
+    let e2 = world.create((Tag,));
+
+    // Insert
+    assert!(world.insert(e1, (Tag,)));
+
+    // Query
+    assert_eq!(world.query_one::<&Tag>().get(e0), Some(&Tag));
+    assert_eq!(world.query_all::<(&A, &Tag)>().iter().count(), 2);
+
+    // No dense data is allocated
+    {
+        let tags = world.borrow::<Tag>();
+        assert_eq!(tags.len(), 3);
+        assert_eq!(
+            tags.as_slice().as_ptr(),
+            NonNull::<Tag>::dangling().as_ptr()
+        );
+    }
+
+    // Remove
+    assert_eq!(world.remove::<(Tag,)>(e2), (Some(Tag),));
+    assert!(!world.contains::<&Tag>(e2));
+    assert_eq!(world.borrow::<Tag>().entities(), [e0, e1]);
+}
+
+#[test]
+fn test_clear_component() {
+    struct Counted {
+        _counter: Arc<()>,
+    }
+
+    let counter = Arc::new(());
+    let mut world = World::builder()
+        .add_group::<(A, B)>()
+        .add_group::<(A, B, C)>()
+        .register::<Counted>()
+        .build();
+
+    let entities = (0..4)
+        .map(|i| {
+            world.create((
+                A(i),
+                B(i),
+                C(i),
+                Counted {
+                    _counter: counter.clone(),
+                },
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    world.clear_component::<C>();
+    world.clear_component::<Counted>();
+
+    // Components are dropped, entities remain alive
+    assert!(world.borrow::<C>().is_empty());
+    assert!(world.borrow::<Counted>().is_empty());
+    assert_eq!(Arc::strong_count(&counter), 1);
+    assert!(entities.iter().all(|&e| world.contains_entity(e)));
+
+    // Only the groups containing the component are emptied
+    assert_eq!(
+        world.query_all::<(&A, &B, &C)>().slice().map(|s| s.0.len()),
+        Some(0)
+    );
+    assert_eq!(
+        world.query_all::<(&A, &B)>().slice().map(|s| s.0.len()),
+        Some(4)
+    );
+
+    // Inserting regroups the entities
+    world.insert(entities[2], (C(20),));
+    assert_eq!(
+        world
+            .query_all::<(Entity, &A, &B, &C)>()
+            .slice()
+            .map(|s| s.0.to_vec()),
+        Some(vec![entities[2]])
+    );
+}
+
+#[test]
+fn test_remove_component_many() {
+    let mut world = World::builder()
+        .add_group::<(A, B)>()
+        .add_group::<(A, B, C)>()
+        .build();
+
+    let e0 = world.create((A(0), B(0), C(0)));
+    let e1 = world.create((A(1), B(1)));
+    let e2 = world.create((A(2), B(2), C(2)));
+    let e3 = world.create((A(3), B(3), C(3)));
+    let dead = world.create((A(4), B(4), C(4)));
+    world.destroy(dead);
+
+    let removed = world.remove_component_many::<C>(&[e2, e1, dead, e0, e2]);
+    assert_eq!(removed, [Some(C(2)), None, None, Some(C(0)), None]);
+
+    // Only `e3` remains in the larger group
+    assert_eq!(
+        world
+            .query_all::<(Entity, &A, &B, &C)>()
+            .slice()
+            .map(|s| s.0.to_vec()),
+        Some(vec![e3])
+    );
+    assert_eq!(
+        world.query_all::<(&A, &B)>().slice().map(|s| s.0.len()),
+        Some(4)
+    );
+
+    let removed = world.remove_component_many::<A>(&[e1, e3]);
+    assert_eq!(removed, [Some(A(1)), Some(A(3))]);
+    assert_eq!(
+        world.query_all::<(&A, &B, &C)>().slice().map(|s| s.0.len()),
+        Some(0)
+    );
+    assert_eq!(
+        world
+            .query_all::<(Entity, &A, &B)>()
+            .slice()
+            .map(|s| s.0.len()),
+        Some(2)
+    );
+    assert!(world.contains::<(&A, &B)>(e0));
+    assert!(world.contains::<(&A, &B)>(e2));
+}
+
+#[test]
+fn test_drain_component() {
+    let mut world = World::builder().add_group::<(A, B)>().build();
+    let e0 = world.create((A(0), B(0)));
+    let e1 = world.create((A(1),));
+    let e2 = world.create((A(2), B(2)));
+
+    assert_eq!(
+        world.query_all::<(&A, &B)>().slice().map(|(a, _)| a.len()),
+        Some(2)
+    );
+
+    let mut drained = world.drain_component::<A>().collect::<Vec<_>>();
+    drained.sort_unstable_by_key(|(entity, _)| entity.index);
+    assert_eq!(drained, [(e0, A(0)), (e1, A(1)), (e2, A(2))]);
+
+    // Storage is empty, entities remain alive
+    assert!(world.borrow::<A>().is_empty());
+    assert_eq!(world.borrow::<B>().len(), 2);
+    assert!([e0, e1, e2].iter().all(|&e| world.contains_entity(e)));
+
+    // Group ranges are updated
+    assert_eq!(
+        world.query_all::<(&A, &B)>().slice().map(|(a, _)| a.len()),
+        Some(0)
+    );
+
+    // Regrouping works after draining
+    world.insert(e2, (A(20),));
+    assert_eq!(
+        world
+            .query_all::<(&A, &B)>()
+            .slice()
+            .map(|(a, _)| a.to_vec()),
+        Some(vec![A(20)])
+    );
+}
+
+#[test]
+fn test_replace_storage() {
+    let mut world = World::builder().add_group::<(A, B)>().build();
+    let e0 = world.create((A(0), B(0)));
+    let e1 = world.create((A(1), B(1)));
+    let e2 = world.create((B(2),));
+    let e3 = world.create(());
+    world.destroy(e3);
+
+    world.replace_storage(vec![(e2, A(20)), (e1, A(10)), (e3, A(30))]);
+
+    // Only the new values remain
+    let a = world.borrow::<A>();
+    assert_eq!(a.len(), 2);
+    assert_eq!(a.get(e0), None);
+    assert_eq!(a.get(e1), Some(&A(10)));
+    assert_eq!(a.get(e2), Some(&A(20)));
+    drop(a);
+
+    // Grouping is updated
+    let mut grouped = world
+        .query_all::<(Entity, &A, &B)>()
+        .slice()
+        .unwrap()
+        .0
+        .to_vec();
+
+    grouped.sort_unstable_by_key(|entity| entity.index);
+    assert_eq!(grouped, [e1, e2]);
+}
+
+#[test]
+fn test_sort_component() {
+    let mut world = World::builder()
+        .add_group::<(A, B)>()
+        .register::<C>()
+        .build();
+
+    let entities = [3, 1, 4, 1, 5, 9, 2, 6]
+        .into_iter()
+        .map(|i| world.create((C(i),)))
+        .collect::<Vec<_>>();
+
+    world.delete::<(C,)>(entities[2]);
+
+    // Ungrouped components can be sorted
+    assert!(world.sort_component::<C, _>(C::cmp).is_ok());
+
+    let c = world.borrow::<C>();
+    assert_eq!(c.as_slice(), [C(1), C(1), C(2), C(3), C(5), C(6), C(9)]);
+
+    for (&entity, component) in c.entities().iter().zip(c.as_slice()) {
+        assert_eq!(c.get(entity), Some(component));
+    }
+
+    drop(c);
+
+    let sorted = world.query_all::<&C>().iter().copied().collect::<Vec<_>>();
+    assert_eq!(sorted, [C(1), C(1), C(2), C(3), C(5), C(6), C(9)]);
+
+    // Grouped components are rejected
+    let error = world.sort_component::<A, _>(A::cmp).unwrap_err();
+    assert_eq!(error.component(), ComponentData::new::<A>());
+}
+
+#[test]
+fn test_snapshot_component() {
+    fn assert_send<T: Send>(_: &T) {}
+
+    let mut world = World::builder().register::<A>().register::<B>().build();
+    let entities = world.extend((0..5).map(|i| (A(i),))).to_vec();
+    world.insert(entities[1], (B(1),));
+    world.delete::<(A,)>(entities[3]);
+
+    let snapshot = world.snapshot_component::<A>();
+    assert_send(&snapshot);
+
+    let expected = world
+        .query_all::<(Entity, &A)>()
+        .iter()
+        .map(|(entity, &a)| (entity, a))
+        .collect::<Vec<_>>();
+
+    assert_eq!(snapshot, expected);
+    assert_eq!(snapshot.len(), 4);
+
+    // The snapshot doesn't borrow the world
+    world.delete::<(A,)>(entities[0]);
+    assert_eq!(snapshot[0], (entities[0], A(0)));
+}
+
+#[test]
+fn test_swap_dense_positions() {
+    let mut world = World::builder()
+        .add_group::<(A, B)>()
+        .register::<C>()
+        .build();
+
+    let entities = world.extend((0..5).map(|i| (C(i),))).to_vec();
+    assert!(world.swap_dense_positions::<C>(1, 3).is_ok());
+
+    let c = world.borrow::<C>();
+    assert_eq!(c.entities()[1], entities[3]);
+    assert_eq!(c.entities()[3], entities[1]);
+    assert_eq!(c.as_slice(), [C(0), C(3), C(2), C(1), C(4)]);
+
+    // Lookups are unaffected
+    for (i, &entity) in entities.iter().enumerate() {
+        assert_eq!(c.get(entity), Some(&C(i as u32)));
+    }
+
+    drop(c);
+
+    // Swapping a position with itself has no effect
+    assert!(world.swap_dense_positions::<C>(2, 2).is_ok());
+    assert_eq!(world.borrow::<C>().entities()[2], entities[2]);
+
+    // Grouped components are rejected
+    let error = world.swap_dense_positions::<A>(0, 1).unwrap_err();
+    assert_eq!(error.component(), ComponentData::new::<A>());
+}
+
+#[test]
+fn test_truncate_component() {
+    let mut world = World::builder()
+        .add_group::<(A, B)>()
+        .register::<C>()
+        .build();
+
+    let entities = world.extend((0..10).map(|i| (C(i),))).to_vec();
+    world.delete::<(C,)>(entities[1]);
+
+    let kept = world.borrow::<C>().entities()[..3].to_vec();
+    assert!(world.truncate_component::<C>(3).is_ok());
+
+    // Only the first components in dense order remain
+    let c = world.borrow::<C>();
+    assert_eq!(c.entities(), kept);
+
+    for &entity in &entities {
+        assert_eq!(c.contains(entity), kept.contains(&entity));
+    }
+
+    drop(c);
+
+    // Entities keep existing
+    assert!(entities.iter().all(|&entity| world.contains_entity(entity)));
+
+    // Truncating to a greater length has no effect
+    assert!(world.truncate_component::<C>(5).is_ok());
+    assert_eq!(world.borrow::<C>().len(), 3);
+
+    // Grouped components are rejected
+    let error = world.truncate_component::<A>(0).unwrap_err();
+    assert_eq!(error.component(), ComponentData::new::<A>());
+}
+
+#[test]
+fn test_prune_entity_refs() {
+    struct Target(Option<Entity>);
+
+    let mut world = World::builder().register::<Target>().build();
+    let e0 = world.create(());
+    let e1 = world.create(());
+    let t0 = world.create((Target(Some(e0)),));
+    let t1 = world.create((Target(Some(e1)),));
+    let t2 = world.create((Target(None),));
+
+    world.destroy(e1);
+    world.prune_entity_refs::<Target, _>(|target| &mut target.0);
+
+    let targets = world.borrow::<Target>();
+    assert_eq!(targets[t0].0, Some(e0));
+    assert_eq!(targets[t1].0, None);
+    assert_eq!(targets[t2].0, None);
+}
+
+#[test]
+fn test_get_component_raw() {
+    let mut world = World::builder()
+        .register_dyn(ComponentData::new::<A>())
+        .build();
+
+    let e0 = world.create((A(1),));
+    let e1 = world.create((A(2),));
+    let e2 = world.create(());
+
+    let type_id = TypeId::of::<A>();
+    let ptr = world.get_component_raw(type_id, e1).unwrap();
+    let bytes = unsafe { slice::from_raw_parts(ptr, mem::size_of::<A>()) };
+    assert_eq!(bytes, 2_u32.to_ne_bytes());
+
+    unsafe {
+        ptr.copy_from_nonoverlapping(20_u32.to_ne_bytes().as_ptr(), mem::size_of::<A>());
+    }
+
+    assert_eq!(world.borrow::<A>()[e1], A(20));
+    assert_eq!(world.borrow::<A>()[e0], A(1));
+
+    // Missing components and types
+    assert!(world.get_component_raw(type_id, e2).is_none());
+    assert!(world.get_component_raw(TypeId::of::<B>(), e0).is_none());
+}
+
+#[test]
+fn test_component_slice_raw() {
+    let mut world = World::builder().register::<A>().register::<B>().build();
+    let e0 = world.create((A(0),));
+    let e1 = world.create((A(1), B(1)));
+    let e2 = world.create((A(2),));
+
+    let type_id = TypeId::of::<A>();
+    let generation = world.component_structural_generation(type_id).unwrap();
+    let (ptr, len, size) = world.component_slice_raw(type_id).unwrap();
+    assert_eq!((len, size), (3, mem::size_of::<A>()));
+
+    let read = |ptr: NonNull<u8>, i: usize| unsafe { ptr.add(i * size).cast::<A>().read() };
+    let components = (0..len).map(|i| read(ptr, i)).collect::<Vec<_>>();
+    assert_eq!(*world.entities_with::<A>().unwrap(), [e0, e1, e2]);
+    assert_eq!(components, [A(0), A(1), A(2)]);
+
+    // Modifying components in place keeps the generation
+    world.borrow_mut::<A>()[e1] = A(10);
+    assert_eq!(
+        world.component_structural_generation(type_id),
+        Some(generation)
+    );
+    assert_eq!(read(ptr, 1), A(10));
+
+    // Structural changes bump the generation
+    world.delete::<(A,)>(e0);
+    assert_ne!(
+        world.component_structural_generation(type_id),
+        Some(generation)
+    );
+
+    let (ptr, len, _) = world.component_slice_raw(type_id).unwrap();
+    let components = (0..len).map(|i| read(ptr, i)).collect::<Vec<_>>();
+    assert_eq!(components.len(), 2);
+    assert!(components.contains(&A(10)));
+    assert!(components.contains(&A(2)));
+
+    // Unregistered types
+    assert!(world.component_slice_raw(TypeId::of::<C>()).is_none());
+    assert!(world
+        .component_structural_generation(TypeId::of::<C>())
+        .is_none());
+}
+
+#[test]
+fn test_check_invariants() {
+    let mut world = World::builder()
+        .add_group::<(A, B)>()
+        .add_group::<(A, B, C)>()
+        .register::<D>()
+        .build();
+
+    assert_eq!(world.check_invariants(), Ok(()));
+
+    let entities = (0..20)
+        .map(|i| match i % 4 {
+            0 => world.create((A(i), B(i))),
+            1 => world.create((A(i), B(i), C(i))),
+            2 => world.create((A(i), C(i), D(i))),
+            _ => world.create((B(i), D(i))),
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(world.check_invariants(), Ok(()));
+
+    for &entity in entities.iter().step_by(3) {
+        world.destroy(entity);
+    }
+
+    world.delete::<(C,)>(entities[5]);
+    world.insert(entities[2], (B(100),));
+    let _ = world.remove_component_many::<A>(&entities[8..12]);
+    world.sort_component::<D, _>(|a, b| b.cmp(a)).unwrap();
+    world.truncate_component::<D>(2).unwrap();
+    assert_eq!(world.check_invariants(), Ok(()));
+
+    world.clear_component::<C>();
+    assert_eq!(world.check_invariants(), Ok(()));
+
+    world.clear();
+    assert_eq!(world.check_invariants(), Ok(()));
+}
+
+#[test]
+fn test_required_components() {
+    #[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+    struct Position(u32);
+
+    #[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+    struct Velocity(u32);
+
+    #[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+    struct Mass(u32);
+
+    let mut world = World::builder()
+        .register::<Position>()
+        .register::<Velocity>()
+        .register::<Mass>()
+        .build();
+
+    assert!(world.register_required::<Velocity, Position>().is_ok());
+    assert!(world.register_required::<Position, Mass>().is_ok());
+
+    // Cycles are rejected
+    let error = world.register_required::<Mass, Velocity>().unwrap_err();
+    assert_eq!(error.component(), ComponentData::new::<Mass>());
+    assert_eq!(error.required(), ComponentData::new::<Velocity>());
+    assert!(world.register_required::<Mass, Mass>().is_err());
+
+    // Required components are inserted transitively
+    let e0 = world.create((Velocity(1),));
+    assert_eq!(world.borrow::<Position>().get(e0), Some(&Position(0)));
+    assert_eq!(world.borrow::<Mass>().get(e0), Some(&Mass(0)));
+
+    // Existing components are kept
+    let e1 = world.create((Position(2), Mass(2)));
+    world.insert(e1, (Velocity(2),));
+    assert_eq!(world.borrow::<Position>()[e1], Position(2));
+    assert_eq!(world.borrow::<Mass>()[e1], Mass(2));
+
+    // Extending inserts required components for all entities
+    let entities = world.extend((0..3).map(|i| (Velocity(i),))).to_vec();
+    for entity in entities {
+        assert!(world.contains::<(&Position, &Mass)>(entity));
+    }
+
+    // Components without requirements are unaffected
+    let e2 = world.create((Mass(3),));
+    assert!(!world.contains::<&Position>(e2));
+}
+
+#[test]
+fn test_required_components_unregistered() {
+    #[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+    struct Position(u32);
+
+    #[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+    struct Velocity(u32);
+
+    let mut world = World::default();
+    assert!(world.register_required::<Velocity, Position>().is_ok());
+    assert!(world.is_registered::<Velocity>());
+    assert!(world.is_registered::<Position>());
+
+    let e0 = world.create((Velocity(1),));
+    assert_eq!(world.borrow::<Position>().get(e0), Some(&Position(0)));
+}
@@ -1,7 +1,11 @@
+//! Tests for registering component types.
+
 mod common;
 
 use self::common::*;
-use sparsey::World;
+use sparsey::component::{ComponentData, GroupLayout};
+use sparsey::{Entity, World};
+use std::any::TypeId;
 
 #[test]
 fn test_components_register() {
@@ -30,3 +34,152 @@ fn test_components_register() {
     assert!(world.is_registered::<A>());
     assert!(world.is_registered::<B>());
 }
+
+#[test]
+fn test_components_register_paged() {
+    let mut world = World::builder()
+        .register_dyn(ComponentData::new::<A>().with_sparse_page_len(16))
+        .register::<B>()
+        .build();
+
+    let entities = world.extend((0..100).map(|i| (A(i), B(i)))).to_vec();
+
+    // Sparse lookups work across pages
+    for &entity in &entities {
+        assert_eq!(world.borrow::<A>().get(entity), Some(&A(entity.index)));
+    }
+
+    // Queries mixing paged and flat storages see every entity
+    assert_eq!(world.query_all::<(&A, &B)>().iter().count(), 100);
+
+    world.delete::<(A,)>(entities[50]);
+    assert!(!world.contains::<&A>(entities[50]));
+    assert!(world.contains::<&B>(entities[50]));
+}
+
+#[test]
+fn test_components_register_twice() {
+    let mut world = World::default();
+
+    // First registration succeeds
+    assert!(world.register::<A>());
+    let e0 = world.create((A(0),));
+
+    // Second registration is a no-op
+    assert!(!world.register::<A>());
+    assert!(!world.register_dyn(ComponentData::new::<A>()));
+    assert_eq!(world.borrow::<A>().get(e0), Some(&A(0)));
+}
+
+#[test]
+fn test_components_register_layout() {
+    let mut layout = GroupLayout::default();
+    layout.add_group::<(A, B)>();
+
+    // Layout components are registered by the layout
+    let mut world = World::new(&layout);
+    assert!(world.is_registered::<A>());
+    assert!(world.is_registered::<B>());
+
+    // Registering them again keeps their grouped storages
+    assert!(!world.register::<A>());
+    assert!(!world.register_dyn(ComponentData::new::<B>()));
+    assert!(world.register::<C>());
+
+    let e0 = world.create((A(0), B(0), C(0)));
+    let _e1 = world.create((A(1), C(1)));
+    let e2 = world.create((A(2), B(2)));
+
+    let mut query = world.query_all::<(Entity, &A, &B)>();
+    assert!(query.is_dense());
+    assert_eq!(query.slice().map(|s| s.0.len()), Some(2));
+    assert!(query.iter().all(|(e, ..)| e == e0 || e == e2));
+    drop(query);
+
+    // Components registered before the layout are moved into their groups
+    let mut world = World::builder().register::<A>().register::<B>().build();
+    let e0 = world.create((A(0), B(0)));
+    world.set_layout(&layout);
+
+    assert!(!world.register::<B>());
+    assert!(world.query_all::<(&A, &B)>().is_dense());
+    assert_eq!(
+        world.query_all::<(&A, &B)>().slice().map(|s| s.0.len()),
+        Some(1)
+    );
+    assert_eq!(world.borrow::<B>().get(e0), Some(&B(0)));
+}
+
+#[test]
+fn test_auto_group() {
+    let mut world = World::builder()
+        .register::<A>()
+        .register::<B>()
+        .register::<C>()
+        .register_dyn(ComponentData::new::<D>().with_sparse_page_len(16))
+        .register::<E>()
+        .build();
+
+    let e0 = world.create((A(0), B(0), C(0)));
+    let e1 = world.create((A(1), B(1), D(1)));
+    assert!(!world.query_is_dense::<(&A, &B)>());
+
+    let ab = [TypeId::of::<A>(), TypeId::of::<B>()];
+    let abc = [TypeId::of::<A>(), TypeId::of::<B>(), TypeId::of::<C>()];
+    let de = [TypeId::of::<D>(), TypeId::of::<E>()];
+
+    world.auto_group(&[&ab, &abc, &de]);
+    assert!(world.query_is_dense::<(&A, &B)>());
+    assert!(world.query_is_dense::<(&A, &B, &C)>());
+    assert!(world.query_is_dense::<(&D, &E)>());
+    assert!(!world.query_is_dense::<(&A, &D)>());
+
+    // Existing entities are grouped
+    let mut query = world.query_all::<(Entity, &A, &B)>();
+    assert_eq!(query.slice().map(|s| s.0.len()), Some(2));
+    assert!(query.iter().all(|(e, ..)| e == e0 || e == e1));
+    drop(query);
+
+    // Components keep their storages
+    assert_eq!(world.borrow::<D>().get(e1), Some(&D(1)));
+
+    // Replacing the layout
+    world.auto_group(&[]);
+    assert!(!world.query_is_dense::<(&A, &B)>());
+    assert_eq!(world.query_all::<(&A, &B)>().iter().count(), 2);
+}
+
+#[test]
+#[should_panic = "was not registered"]
+fn test_auto_group_unregistered() {
+    let mut world = World::builder().register::<A>().build();
+    world.auto_group(&[&[TypeId::of::<A>(), TypeId::of::<B>()]]);
+}
+
+#[test]
+fn test_component_ids() {
+    let mut world = World::builder().register::<A>().register::<B>().build();
+    let e0 = world.create((A(0), B(0)));
+
+    // Assign stable ids
+    assert!(world.assign_component_id::<A>(1));
+    assert!(world.assign_component_id::<B>(2));
+    assert!(world.assign_component_id::<A>(1));
+
+    // Ids and types can't be assigned twice
+    assert!(!world.assign_component_id::<A>(3));
+    assert!(!world.assign_component_id::<C>(2));
+
+    // Round-trip the stable ids
+    assert_eq!(world.component_id::<A>(), Some(1));
+    assert_eq!(world.component_id::<B>(), Some(2));
+    assert_eq!(world.component_id::<C>(), None);
+    assert_eq!(world.component_type_for_id(1), Some(TypeId::of::<A>()));
+    assert_eq!(world.component_type_for_id(2), Some(TypeId::of::<B>()));
+    assert_eq!(world.component_type_for_id(3), None);
+
+    // Resolve the storage
+    let type_id = world.component_type_for_id(2).unwrap();
+    assert!(world.is_registered_dyn(type_id));
+    assert!(world.contains::<&B>(e0));
+}
@@ -1,4 +1,14 @@
+//! Tests for creating and destroying entities.
+
+mod common;
+
+use common::*;
+use sparsey::component::{ComponentData, GroupLayout};
+use sparsey::entity::{Entity, Version};
 use sparsey::World;
+use std::num::NonZeroU32;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
 #[test]
 fn test_entities_crud() {
@@ -38,3 +48,560 @@ fn test_entities_crud() {
     assert!(!world.contains_entity(e1));
     assert_eq!(world.entities(), &[]);
 }
+
+#[test]
+fn test_index_retired_callback() {
+    let retired = Arc::new(AtomicUsize::new(0));
+
+    let mut world = World::default();
+    world.on_index_retired({
+        let retired = retired.clone();
+        move |_| {
+            retired.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+
+    // Recycling with versions left does not retire the index
+    for _ in 0..10 {
+        let e = world.create(());
+        assert!(world.destroy(e));
+    }
+
+    assert_eq!(retired.load(Ordering::Relaxed), 0);
+}
+
+#[test]
+fn test_despawn_with() {
+    let mut world = World::builder().register::<A>().register::<B>().build();
+    let e0 = world.create((A(0),));
+    let e1 = world.create((A(1), B(1)));
+    let e2 = world.create((B(2),));
+    let e3 = world.create(());
+
+    assert_eq!(world.despawn_with::<A>(), 2);
+    assert!(!world.contains_entity(e0));
+    assert!(!world.contains_entity(e1));
+    assert!(world.contains_entity(e2));
+    assert!(world.contains_entity(e3));
+
+    // Other components of destroyed entities are removed
+    assert_eq!(world.borrow::<B>().get(e1), None);
+    assert_eq!(world.borrow::<B>().get(e2), Some(&B(2)));
+
+    assert_eq!(world.despawn_with::<A>(), 0);
+    assert_eq!(world.despawn_with::<C>(), 0);
+}
+
+#[test]
+fn test_extend_strict() {
+    let mut world = World::builder()
+        .add_group::<(A, B)>()
+        .register::<C>()
+        .build();
+
+    // Layout members are accepted
+    let entities = world
+        .extend_strict((0..3).map(|i| (A(i), B(i))))
+        .unwrap()
+        .to_vec();
+
+    assert_eq!(entities.len(), 3);
+    assert_eq!(world.query_all::<(&A, &B)>().iter().count(), 3);
+
+    // Ungrouped components are rejected and no entities are created
+    let error = world
+        .extend_strict((0..3).map(|i| (A(i), C(i))))
+        .unwrap_err();
+
+    assert_eq!(error.component(), ComponentData::new::<C>());
+    assert_eq!(world.entities().len(), 3);
+}
+
+#[test]
+fn test_clear_keeps_layout() {
+    let mut world = World::builder()
+        .add_group::<(A, B)>()
+        .register::<C>()
+        .build();
+
+    world.create((A(0), B(0), C(0)));
+    world.create((A(1),));
+    world.clear();
+
+    // Entities and components are removed
+    assert!(world.is_empty());
+    assert!(world.borrow::<A>().is_empty());
+    assert!(world.borrow::<C>().is_empty());
+
+    // Storages remain registered
+    assert!(world.is_registered::<A>());
+    assert!(world.is_registered::<B>());
+    assert!(world.is_registered::<C>());
+
+    // Layout still applies to new entities
+    world.create((A(2), B(2)));
+    world.create((A(3),));
+
+    let mut query = world.query_all::<(&A, &B)>();
+    assert!(query.iter().is_dense());
+    assert_eq!(query.slice().map(|(a, _)| a.to_vec()), Some(vec![A(2)]));
+}
+
+#[test]
+fn test_extend_mapped() {
+    let mut world = World::builder().add_group::<(A, B)>().build();
+    world.create((A(100),));
+
+    let mapped = world.extend_mapped((0..4).map(|i| (A(i), B(i * 10))), |entity, (a, b)| {
+        (entity, a.0 + b.0)
+    });
+
+    assert_eq!(mapped.len(), 4);
+
+    for (i, &(entity, value)) in mapped.iter().enumerate() {
+        let i = i as u32;
+        assert_eq!(value, i * 11);
+        assert_eq!(
+            world.query_one::<(&A, &B)>().get(entity),
+            Some((&A(i), &B(i * 10)))
+        );
+    }
+
+    // Components are grouped
+    assert_eq!(
+        world.query_all::<(&A, &B)>().slice().map(|s| s.0.len()),
+        Some(4)
+    );
+}
+
+#[test]
+fn test_spawn_empty() {
+    let mut world = World::builder()
+        .add_group::<(A, B)>()
+        .register::<C>()
+        .register::<D>()
+        .build();
+
+    let spawn = |world: &mut World, has_b: bool, has_c: bool| {
+        let mut entity = world.spawn_empty();
+        entity.insert((A(0),));
+
+        if has_b {
+            entity.insert((B(1),));
+        }
+
+        if has_c {
+            entity.insert((C(2),)).insert((C(3),));
+        }
+
+        entity.id()
+    };
+
+    let e0 = spawn(&mut world, true, true);
+    let e1 = spawn(&mut world, false, true);
+    let e2 = world.spawn_empty().id();
+
+    assert!(world.contains_entity(e2));
+    assert!(!world.contains::<&A>(e2));
+
+    assert!(world.contains::<(&A, &B)>(e0));
+    assert_eq!(world.borrow::<C>().get(e0), Some(&C(3)));
+    assert!(!world.contains::<&D>(e0));
+
+    assert!(world.contains::<(&A, &C)>(e1));
+    assert!(!world.contains::<&B>(e1));
+
+    // Grouped components are iterated densely
+    let mut query = world.query_all::<(Entity, &A, &B)>();
+    assert!(query.iter().is_dense());
+    assert_eq!(query.iter().map(|(e, ..)| e).collect::<Vec<_>>(), [e0]);
+}
+
+#[test]
+fn test_entity_handles() {
+    let mut world = World::builder()
+        .add_group::<(A, B)>()
+        .register::<C>()
+        .build();
+
+    let e0 = world.create((A(0), B(0)));
+    let e1 = world.create((C(1),));
+    world.destroy(e1);
+
+    assert!(world.entity(e1).is_none());
+    assert!(world.entity_mut(e1).is_none());
+
+    let mut entity = world.entity_mut(e0).unwrap();
+    assert_eq!(entity.id(), e0);
+    assert!(entity.contains::<(&A, &B)>());
+    assert!(entity.get::<C>().is_none());
+
+    entity.insert((C(0),));
+    entity.get_mut::<A>().unwrap().0 += 10;
+    assert_eq!(*entity.get::<A>().unwrap(), A(10));
+    assert_eq!(entity.remove::<(B,)>(), (Some(B(0)),));
+    assert!(!entity.contains::<&B>());
+
+    entity.insert((B(20),)).delete::<(C,)>();
+    assert!(entity.contains::<(&A, &B)>());
+    assert!(!entity.contains::<&C>());
+
+    // Grouping is kept up to date
+    assert_eq!(
+        world.query_all::<(&A, &B)>().slice().map(|s| s.0.len()),
+        Some(1)
+    );
+
+    let entity = world.entity(e0).unwrap();
+    let (a, b) = (entity.get::<A>().unwrap(), entity.get::<B>().unwrap());
+    assert_eq!((*a, *b), (A(10), B(20)));
+    assert!(entity.get::<C>().is_none());
+    assert!(entity.contains::<(&A, &B)>());
+    assert_eq!(format!("{:?}", *a), "A(10)");
+}
+
+#[test]
+fn test_spawn_template() {
+    let mut world = World::builder()
+        .register::<A>()
+        .register::<B>()
+        .register::<C>()
+        .build();
+
+    assert!(world.register_template("ab", |world, entity| {
+        world.insert(entity, (A(1), B(2)));
+    }));
+
+    // Templates can spawn other templates
+    assert!(world.register_template("abc", |world, entity| {
+        let parent = world.spawn_template("ab").unwrap();
+        world.insert(entity, (C(parent.index),));
+    }));
+
+    let e0 = world.spawn_template("ab").unwrap();
+    assert_eq!(world.borrow::<A>().get(e0), Some(&A(1)));
+    assert_eq!(world.borrow::<B>().get(e0), Some(&B(2)));
+    assert!(!world.contains::<&C>(e0));
+
+    let e1 = world.spawn_template("abc").unwrap();
+    let parent = world.entities()[2];
+    assert!(world.contains::<(&A, &B)>(parent));
+    assert_eq!(world.borrow::<C>().get(e1), Some(&C(parent.index)));
+
+    // Templates can be replaced
+    assert!(!world.register_template("ab", |world, entity| {
+        world.insert(entity, (A(3),));
+    }));
+
+    let e2 = world.spawn_template("ab").unwrap();
+    assert_eq!(world.borrow::<A>().get(e2), Some(&A(3)));
+    assert!(!world.contains::<&B>(e2));
+
+    // Missing templates don't create entities
+    let len = world.entities().len();
+    assert_eq!(world.spawn_template("missing"), None);
+    assert_eq!(world.entities().len(), len);
+}
+
+#[test]
+fn test_defer() {
+    let mut world = World::builder().register::<A>().register::<B>().build();
+    let e0 = world.create((A(0),));
+    let e1 = world.create((A(1),));
+
+    let (e2, seen) = world.defer(|world| {
+        let seen = world
+            .query_all::<(Entity, &A)>()
+            .iter()
+            .map(|(entity, a)| (entity, *a))
+            .collect::<Vec<_>>();
+
+        for &(entity, _) in &seen {
+            world.destroy(entity);
+        }
+
+        let e2 = world.create((A(2), B(2)));
+        world.insert(e0, (B(0),));
+        world.delete::<(A,)>(e0);
+
+        // Queued changes are not visible inside the scope
+        assert!(world.contains_entity(e0));
+        assert!(world.contains_entity(e1));
+        assert!(!world.contains_entity(e2));
+        assert_eq!(world.queued_len(), 5);
+
+        (e2, seen)
+    });
+
+    assert_eq!(seen.len(), 2);
+    assert!(seen.contains(&(e0, A(0))));
+    assert!(seen.contains(&(e1, A(1))));
+
+    // Changes are applied in order when the scope ends
+    assert!(!world.contains_entity(e0));
+    assert!(!world.contains_entity(e1));
+    assert!(world.contains_entity(e2));
+    assert!(world.contains::<(&A, &B)>(e2));
+    assert_eq!(world.query_all::<&A>().iter().count(), 1);
+}
+
+#[test]
+fn test_remap_indices() {
+    let mut world = World::builder()
+        .add_group::<(A, B)>()
+        .register::<C>()
+        .build();
+
+    let e0 = world.create((A(0), B(0)));
+    let e1 = world.create((A(1), C(1)));
+    let e2 = world.create((A(2), B(2), C(2)));
+    world.destroy(e1);
+    let e1 = world.create((A(1), C(1)));
+
+    // Swap the indices of `e0` and `e1`
+    world.remap_indices(|index| match index {
+        0 => 1,
+        1 => 0,
+        index => index,
+    });
+
+    let new_e0 = Entity::new(1, e0.version);
+    let new_e1 = Entity::new(0, e1.version);
+
+    assert!(world.contains_entity(new_e0));
+    assert!(world.contains_entity(new_e1));
+    assert!(world.contains_entity(e2));
+    assert!(!world.contains_entity(e1));
+
+    assert_eq!(
+        world.query_one::<(&A, &B)>().get(new_e0),
+        Some((&A(0), &B(0)))
+    );
+    assert_eq!(
+        world.query_one::<(&A, &C)>().get(new_e1),
+        Some((&A(1), &C(1)))
+    );
+    assert!(!world.contains::<&B>(new_e1));
+
+    // Groups follow the entities
+    let mut grouped = world
+        .query_all::<(Entity, &A, &B)>()
+        .slice()
+        .unwrap()
+        .0
+        .to_vec();
+    grouped.sort_unstable_by_key(|e| e.index);
+    assert_eq!(grouped, [new_e0, e2]);
+
+    // Destroying and recreating entities still works
+    assert!(world.destroy(new_e1));
+    assert!(world.borrow::<C>().get(new_e1).is_none());
+    let e3 = world.create((C(3),));
+    assert_eq!(e3.index, 0);
+    assert_eq!(world.borrow::<C>().get(e3), Some(&C(3)));
+}
+
+#[test]
+fn test_remap_indices_compact() {
+    let mut world = World::builder().register::<A>().build();
+    let e0 = world.create((A(0),));
+    let e1 = world.create((A(1),));
+    let e2 = world.create((A(2),));
+    let e3 = world.create((A(3),));
+    let e4 = world.create((A(4),));
+    world.destroy(e1);
+    world.destroy(e3);
+
+    // Move entities onto free indices
+    world.remap_indices(|index| match index {
+        2 => 1,
+        4 => 2,
+        index => index,
+    });
+
+    let new_e2 = Entity::new(1, e2.version);
+    let new_e4 = Entity::new(2, e4.version);
+    assert_eq!(world.borrow::<A>().get(e0), Some(&A(0)));
+    assert_eq!(world.borrow::<A>().get(new_e2), Some(&A(2)));
+    assert_eq!(world.borrow::<A>().get(new_e4), Some(&A(4)));
+    assert!(!world.contains_entity(e4));
+    assert_eq!(world.index_reuse_histogram(), [0, 0, 0, 1, 1]);
+    assert_eq!(world.check_invariants(), Ok(()));
+
+    // Move an entity past the allocated indices
+    world.remap_indices(|index| if index == 0 { 7 } else { index });
+
+    let new_e0 = Entity::new(7, e0.version);
+    assert_eq!(world.borrow::<A>().get(new_e0), Some(&A(0)));
+    assert_eq!(world.index_reuse_histogram(), [1, 0, 0, 1, 1, 0, 0, 0]);
+
+    // Freed indices are reused, occupied ones are not
+    let mut indices = (0..5)
+        .map(|_| world.create((A(5),)).index)
+        .collect::<Vec<_>>();
+
+    indices.sort_unstable();
+    assert_eq!(indices, [0, 3, 4, 5, 6]);
+    assert_eq!(world.create(()).index, 8);
+    assert_eq!(world.check_invariants(), Ok(()));
+}
+
+#[test]
+#[should_panic = "Index 0 is neither the index of an entity in the world nor free"]
+fn test_remap_indices_not_free() {
+    let mut world = World::default();
+    world.create(());
+    world.clear();
+    world.create(());
+    world.remap_indices(|_| 0);
+}
+
+#[test]
+#[should_panic = "Index 0 is mapped more than once"]
+fn test_remap_indices_not_permutation() {
+    let mut world = World::default();
+    world.create(());
+    world.create(());
+    world.remap_indices(|_| 0);
+}
+
+#[test]
+fn test_structural_generation() {
+    let mut world = World::default();
+    let mut generation = world.structural_generation();
+
+    let mut assert_bumped = |world: &World, bumped: bool| {
+        let new_generation = world.structural_generation();
+        assert_eq!(new_generation != generation, bumped);
+        generation = new_generation;
+    };
+
+    world.register::<A>();
+    assert_bumped(&world, true);
+
+    world.register::<A>();
+    assert_bumped(&world, false);
+
+    let e0 = world.create((A(0),));
+    assert_bumped(&world, true);
+
+    // Modifying components in place
+    world.borrow_mut::<A>()[e0] = A(1);
+    world.for_each::<&mut A>(|a| a.0 += 1);
+    assert_bumped(&world, false);
+
+    // Adding and removing components
+    world.register::<B>();
+    assert_bumped(&world, true);
+
+    world.insert(e0, (B(0),));
+    assert_bumped(&world, true);
+
+    world.delete::<(B,)>(e0);
+    assert_bumped(&world, true);
+
+    // Destroying entities
+    assert!(world.destroy(e0));
+    assert_bumped(&world, true);
+
+    assert!(!world.destroy(e0));
+    assert_bumped(&world, false);
+
+    // Changing the layout
+    let e1 = world.create((A(1), B(1)));
+    assert_bumped(&world, true);
+
+    world.set_layout(&{
+        let mut layout = GroupLayout::default();
+        layout.add_group::<(A, B)>();
+        layout
+    });
+    assert_bumped(&world, true);
+
+    assert!(world.contains::<(&A, &B)>(e1));
+    assert_bumped(&world, false);
+}
+
+#[test]
+fn test_maintain_after_create() {
+    let mut world = World::default();
+
+    // Recycled entity created before maintaining keeps its version
+    let e0 = world.create(());
+    world.destroy(e0);
+    let e1 = world.create(());
+    world.maintain();
+    assert_eq!(world.entities(), [e1]);
+
+    // Entity destroyed before maintaining stays destroyed
+    let e2 = world.create(());
+    world.destroy(e2);
+    world.maintain();
+    assert!(!world.contains_entity(e2));
+    assert_eq!(world.entities(), [e1]);
+
+    // Mixing atomic and regular creation
+    let e3 = world.create_atomic();
+    let e4 = world.create(());
+    let e5 = world.create_atomic();
+    let e6 = world.create(());
+    world.destroy(e6);
+    assert_eq!(world.entities(), [e1, e4]);
+
+    world.maintain();
+    assert!(!world.contains_entity(e6));
+
+    let mut entities = world.entities().to_vec();
+    entities.sort_unstable();
+    let mut expected = vec![e1, e3, e4, e5];
+    expected.sort_unstable();
+    assert_eq!(entities, expected);
+}
+
+#[test]
+fn test_index_reuse_histogram() {
+    let mut world = World::default();
+    assert_eq!(world.index_reuse_histogram(), []);
+
+    let e0 = world.create(());
+    let mut e1 = world.create(());
+
+    for _ in 0..3 {
+        world.destroy(e1);
+        e1 = world.create(());
+    }
+
+    assert_eq!(e1.index, 1);
+    assert_eq!(world.index_reuse_histogram(), [0, 3]);
+
+    // Destroyed indices waiting to be recycled are reported
+    world.destroy(e0);
+    assert_eq!(world.index_reuse_histogram(), [1, 3]);
+
+    // Atomically created entities are reported before maintaining
+    let e2 = world.create_atomic();
+    let e3 = world.create_atomic();
+    assert_eq!((e2.index, e3.index), (0, 2));
+    assert_eq!(world.index_reuse_histogram(), [1, 3, 0]);
+
+    world.maintain();
+    assert_eq!(world.index_reuse_histogram(), [1, 3, 0]);
+
+    // Cleared indices can't be reused
+    world.clear();
+    assert_eq!(world.index_reuse_histogram(), [u32::MAX; 3]);
+
+    world.reset();
+    assert_eq!(world.index_reuse_histogram(), []);
+}
+
+#[test]
+fn test_entity_display() {
+    let entity = Entity::new(42, Version(NonZeroU32::new(3).unwrap()));
+    assert_eq!(format!("{entity}"), "42");
+    assert_eq!(format!("{entity:#}"), "42v3");
+
+    let entity = Entity::with_index(7);
+    assert_eq!(format!("{entity}"), "7");
+    assert_eq!(format!("{entity:#}"), "7v1");
+}
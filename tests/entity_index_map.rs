@@ -0,0 +1,48 @@
+//! Tests for maps keyed by entity indexes.
+
+use sparsey::entity::EntityIndexMap;
+use sparsey::World;
+
+#[test]
+fn test_entity_index_map() {
+    let mut world = World::default();
+    let e0 = world.create(());
+    let e1 = world.create(());
+
+    let mut map = EntityIndexMap::new();
+    assert_eq!(map.insert(e0, 0), None);
+    assert_eq!(map.insert(e1, 1), None);
+    assert_eq!(map.insert(e1, 2), Some(1));
+    assert_eq!(map.len(), 2);
+
+    *map.get_mut(e0).unwrap() += 10;
+    assert_eq!(map.get(e0), Some(&10));
+    assert_eq!(map.get(e1), Some(&2));
+
+    assert_eq!(map.remove(e1), Some(2));
+    assert!(!map.contains(e1));
+    assert_eq!(map.len(), 1);
+}
+
+#[test]
+fn test_entity_index_map_stale() {
+    let mut world = World::default();
+    let old_entity = world.create(());
+    world.destroy(old_entity);
+
+    let new_entity = world.create(());
+    assert_eq!(old_entity.index, new_entity.index);
+
+    let mut map = EntityIndexMap::new();
+    map.insert(new_entity, 1);
+
+    // Stale entities don't retrieve the value of newer ones
+    assert_eq!(map.get(old_entity), None);
+    assert_eq!(map.remove(old_entity), None);
+    assert_eq!(map.get(new_entity), Some(&1));
+
+    // Inserting a stale entity replaces the newer value without returning it
+    assert_eq!(map.insert(old_entity, 0), None);
+    assert_eq!(map.get(new_entity), None);
+    assert_eq!(map.iter().collect::<Vec<_>>(), [(old_entity, &0)]);
+}
@@ -0,0 +1,51 @@
+//! Tests for component insert and remove hooks.
+
+mod common;
+
+use common::*;
+use sparsey::{Entity, World};
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_component_hooks() {
+    let mut world = World::builder().add_group::<(A, B)>().build();
+
+    let inserted = Arc::new(Mutex::new(Vec::<(Entity, A)>::new()));
+    let removed = Arc::new(Mutex::new(Vec::<Entity>::new()));
+
+    world.on_insert::<A, _>({
+        let inserted = inserted.clone();
+        move |entity, a| inserted.lock().unwrap().push((entity, *a))
+    });
+
+    world.on_remove::<A, _>({
+        let removed = removed.clone();
+        move |entity| removed.lock().unwrap().push(entity)
+    });
+
+    // Create and insert
+    let e0 = world.create((A(0), B(0)));
+    let e1 = world.create((B(1),));
+    world.insert(e1, (A(1),));
+    world.insert(e1, (A(2),));
+    assert_eq!(
+        *inserted.lock().unwrap(),
+        [(e0, A(0)), (e1, A(1)), (e1, A(2))]
+    );
+    assert!(removed.lock().unwrap().is_empty());
+
+    // Remove, delete and destroy
+    let e2 = world.create((A(3),));
+    let _ = world.remove::<(A,)>(e0);
+    world.delete::<(A,)>(e1);
+    world.delete::<(A,)>(e1);
+    world.destroy(e2);
+    assert_eq!(*removed.lock().unwrap(), [e0, e1, e2]);
+
+    // Clear
+    let e3 = world.create((A(4),));
+    world.clear();
+    assert_eq!(removed.lock().unwrap().last(), Some(&e3));
+    assert_eq!(inserted.lock().unwrap().len(), 5);
+    assert_eq!(removed.lock().unwrap().len(), 4);
+}
@@ -1,11 +1,12 @@
+//! Tests for sparse and dense query iteration.
+
 mod common;
 
 use common::*;
 use sparsey::entity::Entity;
-use sparsey::query::Query;
+use sparsey::query::{Iter, Query};
 use sparsey::World;
 use std::collections::HashSet;
-use std::iter::FromIterator;
 
 #[test]
 fn test_sparse() {
@@ -62,6 +63,426 @@ where
     let entities = iter.collect::<HashSet<_>>();
     assert_eq!(
         entities,
-        HashSet::from_iter(expected_entities.iter().copied()),
+        expected_entities.iter().copied().collect::<HashSet<_>>(),
     );
 }
+
+#[test]
+fn test_sparse_empty_include() {
+    let mut world = World::builder()
+        .register::<A>()
+        .register::<B>()
+        .build();
+
+    world.extend((0..10).map(|i| (A(i),)));
+
+    // The driving entities come from the empty B storage, so nothing is visited
+    let mut query = world.query_all::<&A>().include::<&B>();
+    let mut iter = query.iter();
+    assert!(iter.is_sparse());
+    assert_eq!(iter.size_hint(), (0, Some(0)));
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn test_iter_sorted() {
+    let mut world_a = World::builder().register::<A>().build();
+    let mut world_b = World::builder().register::<A>().build();
+
+    let entities_a = world_a.extend((0..5).map(|i| (A(i),))).to_vec();
+    let entities_b = world_b.extend((0..5).map(|_| ())).to_vec();
+    assert_eq!(entities_a, entities_b);
+
+    // Insert components in a different order and remove one in the middle
+    for (i, &entity) in entities_b.iter().enumerate().rev() {
+        world_b.insert(entity, (A(i as u32),));
+    }
+
+    world_a.delete::<(A,)>(entities_a[1]);
+    world_b.delete::<(A,)>(entities_b[1]);
+
+    let collect = |world: &World| {
+        world
+            .query_all::<(Entity, &A)>()
+            .iter_sorted()
+            .map(|(entity, &a)| (entity, a))
+            .collect::<Vec<_>>()
+    };
+
+    let items = collect(&world_a);
+    assert_eq!(items, collect(&world_b));
+    assert!(items.is_sorted_by_key(|(entity, _)| entity.index));
+    assert_eq!(items.len(), 4);
+}
+
+#[test]
+fn test_iter_sorted_by_key() {
+    #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+    struct Depth(i32);
+
+    let mut world = World::builder()
+        .add_group::<(A, Depth)>()
+        .register::<B>()
+        .build();
+
+    let depths = [3, -1, 7, 3, 0, -5];
+    let entities = depths
+        .iter()
+        .enumerate()
+        .map(|(i, &depth)| world.create((A(i as u32), Depth(depth))))
+        .collect::<Vec<_>>();
+
+    world.create((A(100),));
+    world.insert(entities[4], (B(0),));
+
+    let mut scratch = Vec::new();
+    let mut query = world.query_all::<(Entity, &mut A, &Depth)>();
+
+    let items = query
+        .iter_sorted_by_key(&mut scratch, |(_, _, depth)| *depth)
+        .map(|(entity, a, depth)| {
+            a.0 += 10;
+            (entity, depth.0)
+        })
+        .collect::<Vec<_>>();
+
+    // Equal keys are ordered by entity index
+    let expected = [5, 1, 4, 0, 3, 2].map(|i| (entities[i], depths[i]));
+    assert_eq!(items, expected);
+
+    // The scratch buffer is reused
+    let capacity = scratch.capacity();
+    assert!(capacity >= depths.len());
+
+    let count = query
+        .iter_sorted_by_key(&mut scratch, |(_, _, depth)| Depth(-depth.0))
+        .count();
+
+    assert_eq!(count, depths.len());
+    assert_eq!(scratch.capacity(), capacity);
+    drop(query);
+
+    // Filters apply before sorting
+    let mut query = world.query_all::<(Entity, &Depth)>().exclude::<&B>();
+    let sorted = query
+        .iter_sorted_by_key(&mut scratch, |(_, depth)| Depth(-depth.0))
+        .map(|(entity, _)| entity)
+        .collect::<Vec<_>>();
+
+    assert_eq!(sorted, [2, 0, 3, 1, 5].map(|i| entities[i]));
+    drop(query);
+
+    assert_eq!(world.borrow::<A>()[entities[0]], A(10));
+}
+
+#[test]
+fn test_dense_peek_entity() {
+    let mut world = World::builder().add_group::<(A, B)>().build();
+    world.extend((0..5).map(|i| (A(i), B(i))));
+
+    let expected = world
+        .query_all::<(Entity, &A)>()
+        .iter()
+        .map(|(entity, &a)| (entity, a))
+        .collect::<Vec<_>>();
+
+    let mut query = world.query_all::<(Entity, &A)>().include::<&B>();
+    let Iter::Dense(mut iter) = query.iter() else {
+        panic!("Expected dense iterator");
+    };
+
+    let mut items = Vec::new();
+
+    while let Some(entity) = iter.peek_entity() {
+        let (next_entity, &a) = iter.next().unwrap();
+        assert_eq!(entity, next_entity);
+        items.push((next_entity, a));
+    }
+
+    assert!(iter.next().is_none());
+    assert_eq!(items, expected);
+}
+
+#[test]
+fn test_dense_entities_match_sparse() {
+    let mut sparse_world = World::builder().register::<A>().register::<B>().build();
+    let mut dense_world = World::builder().add_group::<(A, B)>().build();
+
+    for world in [&mut sparse_world, &mut dense_world] {
+        world.create((A(0), B(0)));
+        world.create((A(1),));
+        world.create((A(2), B(2)));
+        world.create((B(3),));
+        world.create((A(4), B(4)));
+    }
+
+    let mut sparse_query = sparse_world.query_all::<(Entity, &A, &B)>();
+    let mut dense_query = dense_world.query_all::<(Entity, &A, &B)>();
+    let sparse_iter = sparse_query.iter();
+    let dense_iter = dense_query.iter();
+    assert!(sparse_iter.is_sparse());
+    assert!(dense_iter.is_dense());
+
+    let sparse_items = sparse_iter
+        .map(|(e, a, b)| (e, *a, *b))
+        .collect::<HashSet<_>>();
+
+    let dense_items = dense_iter
+        .map(|(e, a, b)| (e, *a, *b))
+        .collect::<HashSet<_>>();
+
+    assert_eq!(sparse_items.len(), 3);
+    assert_eq!(sparse_items, dense_items);
+}
+
+#[test]
+fn test_optional_mut() {
+    let ungrouped_world = World::builder().register::<A>().register::<B>().build();
+    let grouped_world = World::builder()
+        .add_group::<(A, B)>()
+        .register::<C>()
+        .build();
+
+    for mut world in [ungrouped_world, grouped_world] {
+        let entities = (0..6)
+            .map(|i| {
+                if i % 2 == 0 {
+                    world.create((A(i), B(i)))
+                } else {
+                    world.create((A(i),))
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let without_a = world.create((B(100),));
+        let mut query = world.query_all::<(&A, Option<&mut B>)>();
+        let mut visited = 0;
+
+        for (a, b) in &mut query {
+            assert_eq!(b.is_some(), a.0 % 2 == 0);
+
+            if let Some(b) = b {
+                b.0 += 10;
+            }
+
+            visited += 1;
+        }
+
+        assert_eq!(visited, entities.len());
+        drop(query);
+
+        let b = world.borrow::<B>();
+
+        for (i, &entity) in entities.iter().enumerate() {
+            let expected = (i % 2 == 0).then_some(B(i as u32 + 10));
+            assert_eq!(b.get(entity).copied(), expected);
+        }
+
+        // Entities outside the query are untouched
+        assert_eq!(b[without_a], B(100));
+    }
+}
+
+#[test]
+fn test_query_is_dense() {
+    let mut world = World::builder()
+        .add_group::<(A, B)>()
+        .add_group::<(A, B, C)>()
+        .register::<D>()
+        .build();
+
+    world.create((A(0), B(0), C(0), D(0)));
+
+    assert!(world.query_is_dense::<(&A, &B)>());
+    assert!(world.query_is_dense::<(&A, &B, &C)>());
+    assert!(world.query_is_dense::<&D>());
+
+    // Adding a component that isn't part of the group
+    assert!(!world.query_is_dense::<(&A, &B, &D)>());
+    assert!(!world.query_is_dense::<(&A, &C)>());
+
+    // Filters
+    let query = world.query_all::<&A>().include::<&B>().exclude::<&C>();
+    assert!(query.is_dense());
+
+    let query = world.query_all::<&A>().exclude::<&D>();
+    assert!(!query.is_dense());
+}
+
+#[test]
+fn test_assert_grouped() {
+    let world = World::builder()
+        .add_group::<(A, B)>()
+        .register::<C>()
+        .build();
+
+    world.assert_grouped::<(&A, &B)>();
+    world.assert_grouped::<(Entity, &mut A, Option<&B>)>();
+    world.assert_grouped::<&C>();
+}
+
+#[test]
+#[should_panic = "Query '(&iterators::common::A, &iterators::common::C)' is not grouped: \
+                  component `iterators::common::C` is not in a group with \
+                  `iterators::common::A`"]
+fn test_assert_grouped_sparse() {
+    let world = World::builder()
+        .add_group::<(A, B)>()
+        .register::<C>()
+        .build();
+
+    world.assert_grouped::<(&A, &C)>();
+}
+
+#[test]
+#[should_panic = "Query '(&iterators::common::A, &mut iterators::common::C)' is not grouped: \
+                  no group contains exactly the components `iterators::common::A`, \
+                  `iterators::common::C`"]
+fn test_assert_grouped_partial() {
+    let world = World::builder()
+        .add_group::<(A, B)>()
+        .add_group::<(A, B, C)>()
+        .build();
+
+    world.assert_grouped::<(&A, &mut C)>();
+}
+
+#[test]
+fn test_iter_combinations() {
+    let mut world = World::builder().register::<A>().register::<B>().build();
+    world.extend((0..4).map(|i| (A(i),)));
+    world.create((B(4),));
+
+    let mut query = world.query_all::<(Entity, &A)>();
+
+    let pairs = query
+        .iter_combinations::<2>()
+        .map(|[(e0, a0), (e1, a1)]| {
+            assert_ne!(e0, e1);
+            (a0.0, a1.0)
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(pairs, [(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)]);
+    assert_eq!(query.iter_combinations::<3>().count(), 4);
+    assert_eq!(query.iter_combinations::<4>().count(), 1);
+    assert_eq!(query.iter_combinations::<5>().count(), 0);
+    assert_eq!(query.iter_combinations::<1>().count(), 4);
+}
+
+#[test]
+fn test_flat_map_collections() {
+    #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+    struct Item(u32);
+
+    struct Inventory(Vec<Item>);
+
+    let mut world = World::builder()
+        .add_group::<(A, Inventory)>()
+        .register::<B>()
+        .build();
+
+    let e0 = world.create((A(0), Inventory(vec![Item(1), Item(2)])));
+    let e1 = world.create((A(1), Inventory(Vec::new())));
+    let e2 = world.create((Inventory(vec![Item(3)]), B(2)));
+    let e3 = world.create((A(3), Inventory(vec![Item(4), Item(5), Item(6)])));
+
+    // Items borrow from the query, so they can outlive each iteration
+    let mut query = world.query_all::<(Entity, &Inventory)>();
+    let items = query
+        .iter()
+        .flat_map(|(entity, inventory)| inventory.0.iter().map(move |item| (entity, item)))
+        .collect::<HashSet<_>>();
+
+    let expected = [
+        (e0, &Item(1)),
+        (e0, &Item(2)),
+        (e2, &Item(3)),
+        (e3, &Item(4)),
+        (e3, &Item(5)),
+        (e3, &Item(6)),
+    ];
+
+    assert_eq!(items, HashSet::from(expected));
+    assert!(items.iter().all(|(entity, _)| *entity != e1));
+    drop(query);
+
+    // Dense iteration yields the same items
+    let mut query = world.query_all::<(Entity, &Inventory)>().include::<&A>();
+    assert!(query.iter().is_dense());
+
+    let items = query
+        .iter()
+        .flat_map(|(entity, inventory)| inventory.0.iter().map(move |item| (entity, *item)))
+        .collect::<Vec<_>>();
+
+    assert_eq!(items.len(), 5);
+    assert!(items.contains(&(e3, Item(6))));
+    assert!(!items.iter().any(|(entity, _)| *entity == e2));
+}
+
+#[test]
+fn test_resume_after_by_ref() {
+    let ungrouped_world = World::builder().register::<A>().register::<B>().build();
+    let grouped_world = World::builder().add_group::<(A, B)>().build();
+
+    for mut world in [ungrouped_world, grouped_world] {
+        world.extend((0..10).map(|i| (A(i), B(i))));
+        world.create((A(10),));
+
+        let mut query = world.query_all::<(Entity, &mut A, &B)>();
+        let mut iter = query.iter();
+
+        let first = iter.by_ref().take(3).collect::<Vec<_>>();
+        assert_eq!(first.len(), 3);
+
+        let mut entities = first.iter().map(|(e, ..)| *e).collect::<HashSet<_>>();
+
+        for (entity, a, b) in iter {
+            assert!(entities.insert(entity));
+            a.0 += b.0;
+        }
+
+        for (_, a, _) in first {
+            a.0 += 100;
+        }
+
+        // Every item was visited exactly once
+        assert_eq!(entities.len(), 10);
+        drop(query);
+
+        let mut values = world
+            .query_all::<&A>()
+            .include::<&B>()
+            .iter()
+            .map(|a| a.0)
+            .collect::<Vec<_>>();
+
+        values.sort_unstable();
+        assert_eq!(values.len(), 10);
+        assert_eq!(values.iter().filter(|&&v| v >= 100).count(), 3);
+    }
+}
+
+#[test]
+fn test_filter_by_external_set() {
+    let ungrouped_world = World::builder().register::<A>().register::<B>().build();
+    let grouped_world = World::builder().add_group::<(A, B)>().build();
+
+    for mut world in [ungrouped_world, grouped_world] {
+        let entities = world.extend((0..10).map(|i| (A(i), B(i)))).to_vec();
+        let selected = entities.iter().step_by(3).copied().collect::<HashSet<_>>();
+
+        let mut query = world.query_all::<(Entity, &mut A, &B)>();
+
+        for (_, a, b) in query.iter().filter(|(e, ..)| selected.contains(e)) {
+            a.0 += b.0 + 100;
+        }
+
+        drop(query);
+
+        for (entity, a) in &mut world.query_all::<(Entity, &A)>() {
+            assert_eq!(a.0 >= 100, selected.contains(&entity));
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! Tests for deterministic parallel folds.
+
+#![cfg(feature = "parallel")]
+
+mod common;
+
+use common::*;
+use sparsey::World;
+
+fn seq_fold(values: &[f64], chunk_size: usize) -> f64 {
+    values
+        .chunks(chunk_size)
+        .map(|chunk| chunk.iter().fold(0.0, |acc, &value| acc + value))
+        .fold(0.0, |acc, value| acc + value)
+}
+
+fn value(a: A) -> f64 {
+    1.0 / (f64::from(a.0) + 1.0)
+}
+
+#[test]
+fn test_par_fold_sparse() {
+    let mut world = World::builder().register::<A>().register::<B>().build();
+    world.extend((0..1000).map(|i| (A(i),)));
+    world.extend((1000..1500).map(|i| (A(i), B(i))));
+
+    let mut query = world.query_all::<&A>();
+    let values = query.iter().map(|&a| value(a)).collect::<Vec<_>>();
+
+    for chunk_size in [1, 7, 64, 2000] {
+        let expected = seq_fold(&values, chunk_size);
+
+        for _ in 0..10 {
+            let sum = query.par_fold(chunk_size, || 0.0, |acc, &a| acc + value(a), |a, b| a + b);
+            assert_eq!(sum.to_bits(), expected.to_bits());
+        }
+    }
+}
+
+#[test]
+fn test_par_fold_dense() {
+    let mut world = World::builder().add_group::<(A, B)>().build();
+    world.extend((0..1000).map(|i| (A(i),)));
+    world.extend((1000..1500).map(|i| (A(i), B(i))));
+
+    let mut query = world.query_all::<(&mut A, &B)>();
+    assert!(query.par_iter().is_dense());
+
+    let values = query.iter().map(|(a, _)| value(*a)).collect::<Vec<_>>();
+    let expected = seq_fold(&values, 16);
+
+    // Items can be mutated while folding
+    let sum = query.par_fold(
+        16,
+        || 0.0,
+        |acc, (a, b)| {
+            let acc = acc + value(*a);
+            a.0 += b.0;
+            acc
+        },
+        |a, b| a + b,
+    );
+
+    assert_eq!(sum.to_bits(), expected.to_bits());
+    assert!(query.iter().all(|(a, b)| a.0 == 2 * b.0));
+}
+
+#[test]
+fn test_par_fold_empty() {
+    let world = World::builder().register::<A>().build();
+    let sum = world
+        .query_all::<&A>()
+        .par_fold(4, || 5, |acc, a| acc + a.0, |a, b| a + b);
+
+    assert_eq!(sum, 5);
+}
@@ -0,0 +1,69 @@
+//! Tests for composing parallel query iterators with rayon.
+
+#![cfg(feature = "parallel")]
+
+mod common;
+
+use common::*;
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+use sparsey::query::ParIter;
+use sparsey::World;
+
+fn create_world(grouped: bool) -> World {
+    let mut builder = World::builder();
+
+    if grouped {
+        builder.add_group::<(A, B)>();
+    } else {
+        builder.register::<A>().register::<B>();
+    }
+
+    let mut world = builder.register::<C>().build();
+
+    for i in 0..1000 {
+        match i % 3 {
+            0 => world.create((A(i), B(i))),
+            1 => world.create((A(i), B(i), C(i))),
+            _ => world.create((A(i),)),
+        };
+    }
+
+    world
+}
+
+#[test]
+fn test_par_iter_combinators() {
+    for grouped in [false, true] {
+        let world = create_world(grouped);
+        let mut query = world.query_all::<(&A, &mut B)>();
+
+        let expected = query.iter().filter(|(a, _)| a.0 % 7 == 0).count();
+        let count = query.par_iter().filter(|(a, _)| a.0 % 7 == 0).count();
+        assert_eq!(count, expected);
+
+        query.par_iter().for_each(|(a, b)| b.0 = a.0 * 2);
+
+        let expected = query.iter().map(|(_, b)| u64::from(b.0)).sum::<u64>();
+        let sum = query
+            .par_iter()
+            .map(|(_, b)| u64::from(b.0))
+            .reduce(|| 0, |a, b| a + b);
+        assert_eq!(sum, expected);
+    }
+}
+
+#[test]
+fn test_par_iter_indexed() {
+    let world = create_world(true);
+    let mut query = world.query_all::<(&A, &B)>();
+
+    let ParIter::Dense(iter) = query.par_iter() else {
+        panic!("Grouped query should be dense");
+    };
+
+    assert_eq!(iter.len(), 667);
+
+    let items = iter.map(|(a, _)| *a).collect::<Vec<_>>();
+    let expected = query.iter().map(|(a, _)| *a).collect::<Vec<_>>();
+    assert_eq!(items, expected);
+}
@@ -0,0 +1,254 @@
+//! Tests for querying single entities.
+
+mod common;
+
+use common::*;
+use sparsey::query::SingleError;
+use sparsey::{Entity, World};
+use std::mem::MaybeUninit;
+
+#[test]
+fn test_gather() {
+    let mut world = World::builder()
+        .register::<A>()
+        .register::<B>()
+        .build();
+
+    let e0 = world.create((A(0), B(0)));
+    let e1 = world.create((A(1),));
+    let e2 = world.create((A(2), B(2)));
+    let e3 = world.create((A(3), B(3)));
+    world.destroy(e3);
+
+    let ((a, b), missing) = world.gather::<(&A, &B), (Vec<A>, Vec<B>)>(&[e2, e1, e3, e0]);
+    assert_eq!(a, [A(2), A(0)]);
+    assert_eq!(b, [B(2), B(0)]);
+    assert_eq!(missing, [1, 2]);
+}
+
+#[test]
+fn test_entities_with() {
+    let mut world = World::builder().register::<A>().build();
+    assert!(world.entities_with::<B>().is_none());
+
+    let e0 = world.create((A(0),));
+    let _e1 = world.create(());
+    let e2 = world.create((A(2),));
+    let e3 = world.create((A(3),));
+    world.destroy(e2);
+
+    let entities = world.entities_with::<A>().unwrap();
+    let queried = world
+        .query_all::<(Entity, &A)>()
+        .iter()
+        .map(|(entity, _)| entity)
+        .collect::<Vec<_>>();
+
+    assert_eq!(*entities, queried);
+    assert_eq!(*entities, [e0, e3]);
+}
+
+#[test]
+#[should_panic(expected = "already immutably borrowed")]
+fn test_entities_with_borrows_storage() {
+    let world = World::builder().register::<A>().build();
+    let _entities = world.entities_with::<A>();
+    let _ = world.borrow_mut::<A>();
+}
+
+#[test]
+fn test_entities_without() {
+    let mut world = World::builder().register::<A>().register::<B>().build();
+    assert_eq!(world.entities_without::<A>().count(), 0);
+
+    let e0 = world.create((A(0),));
+    let e1 = world.create((B(1),));
+    let e2 = world.create(());
+    let e3 = world.create((A(3), B(3)));
+    let e4 = world.create((B(4),));
+    world.destroy(e4);
+
+    let without_a = world.entities_without::<A>().collect::<Vec<_>>();
+    assert_eq!(without_a.len(), 2);
+    assert!(without_a.contains(&e1));
+    assert!(without_a.contains(&e2));
+
+    let without_b = world.entities_without::<B>().collect::<Vec<_>>();
+    assert_eq!(without_b.len(), 2);
+    assert!(without_b.contains(&e0));
+    assert!(without_b.contains(&e2));
+
+    world.delete::<(A,)>(e3);
+    assert!(world.entities_without::<A>().any(|e| e == e3));
+}
+
+#[test]
+fn test_iter_over() {
+    let mut world = World::builder()
+        .register::<A>()
+        .register::<B>()
+        .build();
+
+    let e0 = world.create((A(0), B(0)));
+    let e1 = world.create((A(1),));
+    let e2 = world.create((A(2), B(2)));
+    let e3 = world.create((A(3), B(3)));
+    world.destroy(e3);
+
+    let mut query = world.query_one::<(&mut A, &B)>();
+
+    // Non-matching, destroyed and repeated entities are skipped
+    let order = [e2, e1, e3, e0, e2];
+    let items = query.iter_over(&order).map(|(a, _)| *a).collect::<Vec<_>>();
+    assert_eq!(items, [A(2), A(0)]);
+
+    // Reordering the input reorders the output
+    let order = [e0, e2];
+    let items = query.iter_over(&order).map(|(a, _)| *a).collect::<Vec<_>>();
+    assert_eq!(items, [A(0), A(2)]);
+
+    for (a, b) in query.iter_over(&order) {
+        a.0 += b.0 + 10;
+    }
+
+    drop(query);
+    assert_eq!(world.borrow::<A>()[e2], A(14));
+}
+
+#[test]
+fn test_first_single() {
+    let mut world = World::builder().register::<A>().register::<B>().build();
+
+    // None
+    assert_eq!(world.query_all::<&A>().first(), None);
+    assert_eq!(world.query_all::<&A>().single(), Err(SingleError::None));
+
+    // One
+    let e0 = world.create((A(0), B(0)));
+    world.create((B(1),));
+    assert_eq!(world.query_all::<&A>().first(), Some(&A(0)));
+    assert_eq!(world.query_all::<(Entity, &A)>().single(), Ok((e0, &A(0))));
+
+    // Multiple
+    world.create((A(2),));
+    assert!(world.query_all::<&A>().first().is_some());
+    assert_eq!(world.query_all::<&A>().single(), Err(SingleError::Multiple));
+    assert_eq!(world.query_all::<(&A, &B)>().single(), Ok((&A(0), &B(0))));
+}
+
+#[test]
+fn test_write_into() {
+    let mut world = World::builder().register::<A>().register::<B>().build();
+    let entities = world.extend((0..6).map(|i| (A(i),))).to_vec();
+    world.insert(entities[4], (B(4),));
+
+    let mut query = world.query_all::<(Entity, &A)>();
+    let expected = query.iter().map(|(e, &a)| (e, a)).collect::<Vec<_>>();
+
+    // Buffer shorter than the query
+    let mut out = [MaybeUninit::uninit(); 4];
+    assert_eq!(query.write_into(&mut out), 4);
+
+    let written = out.map(|item| {
+        let (e, &a) = unsafe { item.assume_init() };
+        (e, a)
+    });
+
+    assert_eq!(written, expected[..4]);
+
+    // Buffer longer than the query
+    let mut query = world.query_all::<&A>().include::<&B>();
+    let mut out = [MaybeUninit::uninit(); 4];
+    assert_eq!(query.write_into(&mut out), 1);
+    assert_eq!(unsafe { out[0].assume_init() }, &A(4));
+}
+
+#[test]
+fn test_get_stale_entity() {
+    let mut world = World::builder().register::<A>().register::<B>().build();
+
+    let stale = world.create((A(0), B(0)));
+    world.destroy(stale);
+
+    let entity = world.create((A(1), B(1)));
+    assert_eq!(entity.index, stale.index);
+    assert_ne!(entity.version, stale.version);
+
+    // Stale handles don't resolve to the components of the new occupant
+    let mut query = world.query_one::<(&A, &B)>();
+    assert_eq!(query.get(stale), None);
+    assert!(!query.contains(stale));
+    assert_eq!(query.get(entity), Some((&A(1), &B(1))));
+    drop(query);
+
+    let mut query = world.query_one::<&A>().include::<&B>();
+    assert_eq!(query.get(stale), None);
+    assert_eq!(
+        query.iter_over(&[stale, entity]).collect::<Vec<_>>(),
+        [&A(1)]
+    );
+    drop(query);
+
+    assert_eq!(world.borrow::<A>().get(stale), None);
+    assert_eq!(world.borrow_mut::<B>().get_mut(stale), None);
+}
+
+#[cfg(feature = "fixedbitset")]
+#[test]
+fn test_iter_in_set() {
+    use fixedbitset::FixedBitSet;
+
+    let mut world = World::builder()
+        .register::<A>()
+        .register::<B>()
+        .register::<C>()
+        .build();
+
+    let entities = (0..8)
+        .map(|i| match i % 3 {
+            0 => world.create((A(i), B(i))),
+            1 => world.create((A(i),)),
+            _ => world.create((A(i), B(i), C(i))),
+        })
+        .collect::<Vec<_>>();
+
+    world.destroy(entities[5]);
+
+    let mut set = FixedBitSet::with_capacity(16);
+    set.extend([0, 1, 2, 5, 6, 12]);
+
+    let mut query = world.query_one::<(Entity, &A)>().include::<&B>();
+    let items = query
+        .iter_in_set(&set)
+        .map(|(e, a)| (e, *a))
+        .collect::<Vec<_>>();
+    drop(query);
+
+    let expected = world
+        .query_all::<(Entity, &A)>()
+        .include::<&B>()
+        .iter()
+        .filter(|(e, _)| set.contains(e.sparse()))
+        .map(|(e, a)| (e, *a))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        items,
+        [
+            (entities[0], A(0)),
+            (entities[2], A(2)),
+            (entities[6], A(6))
+        ]
+    );
+    assert_eq!(items.len(), expected.len());
+    assert!(expected.iter().all(|item| items.contains(item)));
+
+    let mut query = world.query_one::<&mut A>().exclude::<&C>();
+    for a in query.iter_in_set(&set) {
+        a.0 += 10;
+    }
+
+    drop(query);
+    assert_eq!(world.borrow::<A>()[entities[1]], A(11));
+    assert_eq!(world.borrow::<A>()[entities[2]], A(2));
+}
@@ -0,0 +1,112 @@
+//! Tests for flat and paged sparse vecs.
+
+use sparsey::entity::{Entity, SparseVec, SparseVecSlot, Version};
+
+fn insert(sparse: &mut SparseVec, entity: Entity, index: u32) {
+    *sparse.get_mut_or_allocate_at(entity.sparse()) = Some(SparseVecSlot {
+        index,
+        version: entity.version,
+    });
+}
+
+#[test]
+fn test_sparse_vec_crud() {
+    let e0 = Entity::new(0, Version::FIRST);
+    let e1 = Entity::new(7, Version::FIRST);
+    let stale_e1 = Entity::new(7, Version::LAST);
+
+    let mut sparse = SparseVec::new();
+    assert_eq!(sparse.page_len(), None);
+    assert!(!sparse.contains(e0));
+
+    // Insert
+    insert(&mut sparse, e0, 0);
+    insert(&mut sparse, e1, 1);
+    assert_eq!(sparse.get(e0), Some(0));
+    assert_eq!(sparse.get(e1), Some(1));
+
+    // Versions are checked
+    assert_eq!(sparse.get(stale_e1), None);
+    assert_eq!(sparse.remove(stale_e1), None);
+
+    // Remove
+    assert_eq!(sparse.remove(e1), Some(1));
+    assert_eq!(sparse.get(e1), None);
+    assert_eq!(sparse.get(e0), Some(0));
+}
+
+#[test]
+fn test_paged_sparse_vec_memory() {
+    let e0 = Entity::new(0, Version::FIRST);
+    let e1 = Entity::new(1_000_000, Version::FIRST);
+
+    let mut flat = SparseVec::new();
+    insert(&mut flat, e0, 0);
+    insert(&mut flat, e1, 1);
+
+    let mut paged = SparseVec::paged(1024);
+    insert(&mut paged, e0, 0);
+    insert(&mut paged, e1, 1);
+
+    // Only the two touched pages are allocated
+    assert_eq!(paged.page_len(), Some(1024));
+    assert_eq!(paged.allocated_len(), 2 * 1024);
+    assert!(flat.allocated_len() > 1_000_000);
+
+    for sparse in [&flat, &paged] {
+        assert_eq!(sparse.get(e0), Some(0));
+        assert_eq!(sparse.get(e1), Some(1));
+        assert!(!sparse.contains_sparse(500_000));
+    }
+
+    // Removing entities keeps the pages allocated
+    assert_eq!(paged.remove(e1), Some(1));
+    assert!(!paged.contains(e1));
+    assert_eq!(paged.allocated_len(), 2 * 1024);
+
+    // Clearing frees all pages
+    paged.clear();
+    assert!(!paged.contains(e0));
+    assert_eq!(paged.allocated_len(), 0);
+}
+
+#[test]
+fn test_paged_sparse_vec_swap() {
+    let e0 = Entity::new(3, Version::FIRST);
+    let e1 = Entity::new(5000, Version::FIRST);
+
+    let mut sparse = SparseVec::paged(64);
+    insert(&mut sparse, e0, 0);
+    insert(&mut sparse, e1, 1);
+
+    unsafe {
+        sparse.swap_nonoverlapping(e0.sparse(), e1.sparse());
+    }
+
+    assert_eq!(sparse.get(e0), Some(1));
+    assert_eq!(sparse.get(e1), Some(0));
+}
+
+#[test]
+fn test_sparse_vec_occupancy() {
+    for mut sparse in [SparseVec::new(), SparseVec::paged(2)] {
+        assert_eq!(sparse.occupancy().count(), 0);
+
+        insert(&mut sparse, Entity::with_index(0), 0);
+        insert(&mut sparse, Entity::with_index(2), 1);
+        let occupancy = sparse.occupancy().collect::<Vec<_>>();
+        assert_eq!(occupancy, [true, false, true]);
+
+        // Trailing free slots are not reported
+        sparse.remove_sparse(2);
+        assert_eq!(sparse.occupancy().collect::<Vec<_>>(), [true]);
+    }
+
+    // Unallocated pages are reported as free
+    let mut sparse = SparseVec::paged(4);
+    insert(&mut sparse, Entity::with_index(9), 0);
+    let occupancy = sparse.occupancy().collect::<Vec<_>>();
+    assert_eq!(occupancy.len(), 10);
+    assert_eq!(occupancy.iter().filter(|&&occupied| occupied).count(), 1);
+    assert!(occupancy[9]);
+}
@@ -0,0 +1,203 @@
+//! Tests for borrowing component views.
+
+mod common;
+
+use common::*;
+use sparsey::World;
+
+#[test]
+fn test_view_get_two_mut() {
+    let mut world = World::builder().register::<A>().build();
+    let e0 = world.create((A(0),));
+    let e1 = world.create((A(1),));
+    let e2 = world.create(());
+
+    let mut a = world.borrow_mut::<A>();
+
+    // Same entity is rejected
+    assert!(a.get_two_mut(e0, e0).is_none());
+
+    // Missing component is rejected
+    assert!(a.get_two_mut(e0, e2).is_none());
+
+    // Distinct entities are returned in order
+    let (a0, a1) = a.get_two_mut(e0, e1).unwrap();
+    assert_eq!((*a0, *a1), (A(0), A(1)));
+    a0.0 += 10;
+    a1.0 += 10;
+    assert_eq!(a[e0], A(10));
+    assert_eq!(a[e1], A(11));
+}
+
+#[test]
+fn test_view_replace() {
+    let mut world = World::builder().register::<A>().build();
+    let e0 = world.create((A(0),));
+    let e1 = world.create(());
+
+    let mut a = world.borrow_mut::<A>();
+    let generation = a.structural_generation();
+
+    // Existing components are replaced
+    assert_eq!(a.replace(e0, A(1)), Some(A(0)));
+    assert_eq!(a.replace(e0, A(2)), Some(A(1)));
+    assert_eq!(a[e0], A(2));
+
+    // Missing components are not inserted
+    assert_eq!(a.replace(e1, A(3)), None);
+    assert!(!a.contains(e1));
+
+    // Replacing doesn't change the layout
+    assert_eq!(a.structural_generation(), generation);
+}
+
+#[test]
+fn test_view_is_valid_dense_index() {
+    let mut world = World::builder().register::<A>().build();
+    let entities = world.extend((0..3).map(|i| (A(i),))).to_vec();
+    world.delete::<(A,)>(entities[1]);
+
+    let a = world.borrow::<A>();
+    assert_eq!(a.len(), 2);
+    assert!(a.is_valid_dense_index(0));
+    assert!(a.is_valid_dense_index(1));
+    assert!(!a.is_valid_dense_index(2));
+    assert!(!a.is_valid_dense_index(usize::MAX));
+
+    for i in (0..4).filter(|&i| a.is_valid_dense_index(i)) {
+        assert_eq!(a.get(a.entities()[i]), Some(&a.as_slice()[i]));
+    }
+}
+
+#[test]
+fn test_view_contains() {
+    let mut world = World::builder().register::<A>().build();
+    let e0 = world.create((A(0),));
+    let e1 = world.create(());
+    let e2 = world.create((A(2),));
+    world.destroy(e2);
+    let e3 = world.create(());
+
+    let a = world.borrow::<A>();
+
+    for entity in [e0, e1, e2, e3] {
+        assert_eq!(a.contains(entity), a.get(entity).is_some());
+    }
+}
+
+#[test]
+fn test_view_as_slices() {
+    let mut world = World::builder().register::<A>().build();
+    let entities = world.extend((0..5).map(|i| (A(i),))).to_vec();
+    world.delete::<(A,)>(entities[0]);
+    world.delete::<(A,)>(entities[2]);
+
+    {
+        let a = world.borrow::<A>();
+        let (a_entities, a_components) = a.as_slices();
+        assert_eq!(a_entities.len(), 3);
+        assert_eq!(a_components.len(), 3);
+
+        for (&entity, component) in a_entities.iter().zip(a_components) {
+            assert_eq!(a.get(entity), Some(component));
+        }
+    }
+
+    let mut a = world.borrow_mut::<A>();
+    let (a_entities, a_components) = a.as_mut_slices();
+
+    for (&entity, component) in a_entities.iter().zip(a_components) {
+        component.0 = entity.index;
+    }
+
+    for &entity in &[entities[1], entities[3], entities[4]] {
+        assert_eq!(a[entity], A(entity.index));
+    }
+}
+
+#[test]
+fn test_view_group_info_eq() {
+    let world = World::builder()
+        .add_group::<(A, B)>()
+        .add_group::<(A, B, C)>()
+        .register::<D>()
+        .build();
+
+    let (_, a0) = world.borrow_with_group_info::<A>();
+    let (_, a1) = world.borrow_with_group_info::<A>();
+    let (_, b) = world.borrow_with_group_info::<B>();
+    let (_, d) = world.borrow_with_group_info::<D>();
+
+    // Borrows of the same grouped component are equal
+    assert!(a0.is_some());
+    assert_eq!(a0, a1);
+
+    // Different components are not equal
+    assert_ne!(a0, b);
+    assert_eq!(d, None);
+
+    // Combined group infos are equal regardless of order
+    let ab = a0.unwrap().add_group(b.unwrap());
+    let ba = b.unwrap().add_group(a0.unwrap());
+    assert!(ab.is_some());
+    assert_eq!(ab, ba);
+}
+
+#[test]
+fn test_view_structural_generation() {
+    let mut world = World::builder().add_group::<(A, B)>().build();
+    let generation = |world: &World| world.borrow::<A>().structural_generation();
+
+    // Inserting new components bumps the generation
+    let g0 = generation(&world);
+    let e0 = world.create((A(0),));
+    let g1 = generation(&world);
+    assert!(g1 > g0);
+
+    // Overwriting and writing in place don't
+    world.insert(e0, (A(1),));
+    world.borrow_mut::<A>()[e0].0 = 2;
+    assert_eq!(generation(&world), g1);
+
+    // Grouping bumps the generation when it reorders components
+    world.create((A(3), B(3)));
+    let g2 = generation(&world);
+    assert_eq!(g2, g1 + 2);
+
+    // Removing bumps the generation
+    world.delete::<(A,)>(e0);
+    assert!(generation(&world) > g2);
+}
+
+#[test]
+fn test_with_component_mut() {
+    let mut world = World::builder().register::<A>().register::<B>().build();
+    let e0 = world.create((A(0), B(1)));
+    let e1 = world.create((A(0), B(2)));
+    let e2 = world.create((B(3),));
+
+    // Other components can be queried while modifying the component
+    let result = world.with_component_mut::<A, _>(e0, |a, world| {
+        a.0 = world.query_all::<&B>().iter().map(|b| b.0).sum();
+        a.0
+    });
+
+    assert_eq!(result, Some(6));
+    assert_eq!(world.borrow::<A>()[e0], A(6));
+    assert_eq!(world.borrow::<A>()[e1], A(0));
+
+    // Missing components don't call the closure
+    let result = world.with_component_mut::<A, _>(e2, |_, _| unreachable!());
+    assert_eq!(result, None);
+}
+
+#[test]
+#[should_panic(expected = "already mutably borrowed")]
+fn test_with_component_mut_borrow_same() {
+    let mut world = World::builder().register::<A>().build();
+    let e0 = world.create((A(0),));
+
+    world.with_component_mut::<A, _>(e0, |_, world| {
+        let _ = world.borrow::<A>();
+    });
+}